@@ -0,0 +1,132 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dbn::{Metadata, TradeMsg};
+use indexmap::IndexMap;
+use nautilus_adapters::databento::{
+    stubs::{
+        make_metadata, make_ohlcv_record, make_symbol_mapping, make_trade_record, TS_2024_01_02,
+    },
+    symbology::{
+        decode_nautilus_instrument_id, decode_nautilus_instrument_ids, DatabentoSymbologyResolver,
+    },
+};
+use nautilus_model::identifiers::venue::Venue;
+
+const NUM_INSTRUMENTS: u32 = 500;
+const NUM_RECORDS: u32 = 1_000;
+
+fn metadata() -> Metadata {
+    make_metadata(
+        (0..NUM_INSTRUMENTS)
+            .map(|i| make_symbol_mapping(&format!("SYM{i}"), i))
+            .collect(),
+    )
+}
+
+fn symbology_bench(c: &mut Criterion) {
+    let metadata = metadata();
+    let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+    let glbx_exchange_map = HashMap::new();
+    let msg = make_trade_record(NUM_INSTRUMENTS / 2, 1, TS_2024_01_02);
+    let rec_ref = dbn::RecordRef::from(&msg);
+
+    let mut group = c.benchmark_group("symbology");
+
+    group.bench_function("decode_nautilus_instrument_id", |b| {
+        b.iter(|| {
+            decode_nautilus_instrument_id(
+                black_box(&rec_ref),
+                1,
+                &metadata,
+                &publisher_venue_map,
+                &glbx_exchange_map,
+            )
+            .unwrap()
+        });
+    });
+
+    let msgs: Vec<TradeMsg> = (0..NUM_RECORDS)
+        .map(|i| make_trade_record(i % NUM_INSTRUMENTS, 1, TS_2024_01_02))
+        .collect();
+    let records: Vec<dbn::RecordRef> = msgs.iter().map(dbn::RecordRef::from).collect();
+
+    group.bench_function("decode_nautilus_instrument_id_loop", |b| {
+        b.iter(|| {
+            records
+                .iter()
+                .map(|rec_ref| {
+                    decode_nautilus_instrument_id(
+                        black_box(rec_ref),
+                        1,
+                        &metadata,
+                        &publisher_venue_map,
+                        &glbx_exchange_map,
+                    )
+                    .unwrap()
+                })
+                .collect::<Vec<_>>()
+        });
+    });
+
+    group.bench_function("decode_nautilus_instrument_ids", |b| {
+        b.iter(|| {
+            decode_nautilus_instrument_ids(
+                black_box(&records),
+                &metadata,
+                &publisher_venue_map,
+                &glbx_exchange_map,
+            )
+            .unwrap()
+        });
+    });
+
+    let mut resolver =
+        DatabentoSymbologyResolver::new(metadata.clone(), publisher_venue_map, glbx_exchange_map);
+    group.bench_function("resolver_resolve", |b| {
+        b.iter(|| resolver.resolve(black_box(&rec_ref), 1).unwrap());
+    });
+
+    group.bench_function("resolver_resolve_loop", |b| {
+        b.iter(|| {
+            records
+                .iter()
+                .map(|rec_ref| resolver.resolve(black_box(rec_ref), 1).unwrap())
+                .collect::<Vec<_>>()
+        });
+    });
+    // Raw symbols are interned once per instrument and date, rather than per resolution
+    assert_eq!(resolver.symbol_interns(), u64::from(NUM_INSTRUMENTS));
+
+    resolver.set_instrument_id_cache(true);
+    group.bench_function("resolver_resolve_cached", |b| {
+        b.iter(|| resolver.resolve(black_box(&rec_ref), 1).unwrap());
+    });
+
+    // The cached path is dominated by the record type dispatch
+    let ohlcv = make_ohlcv_record(dbn::rtype::OHLCV_1M, NUM_INSTRUMENTS / 2, 1, TS_2024_01_02);
+    let ohlcv_rec_ref = dbn::RecordRef::from(&ohlcv);
+    group.bench_function("resolver_resolve_cached_ohlcv", |b| {
+        b.iter(|| resolver.resolve(black_box(&ohlcv_rec_ref), 1).unwrap());
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, symbology_bench);
+criterion_main!(benches);
@@ -13,9 +13,13 @@
 //  limitations under the License.
 // -------------------------------------------------------------------------------------------------
 
-use anyhow::Result;
-use databento::historical::DateTimeRange;
+use anyhow::{bail, Result};
+use databento::{dbn::Schema, historical::DateTimeRange};
 use nautilus_core::time::UnixNanos;
+use nautilus_model::{
+    data::bar::BarType,
+    enums::{BarAggregation, PriceType},
+};
 use time::OffsetDateTime;
 
 pub const DATABENTO: &str = "DATABENTO";
@@ -32,3 +36,62 @@ pub fn get_date_time_range(start: UnixNanos, end: Option<UnixNanos>) -> Result<D
         )),
     }
 }
+
+/// Returns the Databento schema (e.g. `ohlcv-1m`) for the given Nautilus `bar_type`.
+///
+/// Databento only provides one-second, one-minute, one-hour and one-day bars aggregated from
+/// trades, so any other step, aggregation (such as tick or volume bars) or price type is
+/// an error.
+pub fn bar_type_to_databento_schema(bar_type: &BarType) -> Result<&'static str> {
+    let spec = &bar_type.spec;
+    if spec.price_type != PriceType::Last {
+        bail!(
+            "Unsupported price type {} for Databento bars in {bar_type}, only LAST is available",
+            spec.price_type
+        );
+    }
+
+    let schema = match (spec.step, spec.aggregation) {
+        (1, BarAggregation::Second) => Schema::Ohlcv1S,
+        (1, BarAggregation::Minute) => Schema::Ohlcv1M,
+        (1, BarAggregation::Hour) => Schema::Ohlcv1H,
+        (1, BarAggregation::Day) => Schema::Ohlcv1D,
+        (step, aggregation) => bail!(
+            "Unsupported bar step {step} and aggregation {aggregation} for Databento bars in \
+             {bar_type}"
+        ),
+    };
+    Ok(schema.as_str())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("ESH4.GLBX-1-SECOND-LAST-EXTERNAL", "ohlcv-1s")]
+    #[case("ESH4.GLBX-1-MINUTE-LAST-EXTERNAL", "ohlcv-1m")]
+    #[case("ESH4.GLBX-1-HOUR-LAST-EXTERNAL", "ohlcv-1h")]
+    #[case("ESH4.GLBX-1-DAY-LAST-EXTERNAL", "ohlcv-1d")]
+    fn test_bar_type_to_databento_schema(#[case] bar_type: &str, #[case] expected: &str) {
+        let bar_type = BarType::from(bar_type);
+
+        assert_eq!(bar_type_to_databento_schema(&bar_type).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("ESH4.GLBX-100-TICK-LAST-EXTERNAL")]
+    #[case("ESH4.GLBX-1000-VOLUME-LAST-EXTERNAL")]
+    #[case("ESH4.GLBX-5-MINUTE-LAST-EXTERNAL")]
+    #[case("ESH4.GLBX-1-MINUTE-MID-EXTERNAL")]
+    fn test_bar_type_to_databento_schema_unsupported(#[case] bar_type: &str) {
+        let bar_type = BarType::from(bar_type);
+
+        assert!(bar_type_to_databento_schema(&bar_type).is_err());
+    }
+}
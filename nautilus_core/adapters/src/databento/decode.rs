@@ -22,6 +22,7 @@ use std::{
 
 use anyhow::{anyhow, bail, Result};
 use databento::dbn::Record;
+use indexmap::IndexMap;
 use nautilus_core::{datetime::NANOSECONDS_IN_SECOND, time::UnixNanos};
 use nautilus_model::{
     data::{
@@ -37,15 +38,22 @@ use nautilus_model::{
         AggregationSource, AggressorSide, AssetClass, BarAggregation, BookAction, InstrumentClass,
         OptionKind, OrderSide, PriceType,
     },
-    identifiers::{instrument_id::InstrumentId, trade_id::TradeId},
+    identifiers::{instrument_id::InstrumentId, symbol::Symbol, trade_id::TradeId, venue::Venue},
     instruments::{
         equity::Equity, futures_contract::FuturesContract, futures_spread::FuturesSpread,
         options_contract::OptionsContract, options_spread::OptionsSpread, Instrument,
     },
-    types::{currency::Currency, fixed::FIXED_SCALAR, price::Price, quantity::Quantity},
+    types::{
+        currency::Currency,
+        fixed::{check_fixed_precision, FIXED_PRECISION, FIXED_SCALAR},
+        price::Price,
+        quantity::Quantity,
+    },
 };
 use ustr::Ustr;
 
+use super::types::PublisherId;
+
 const BAR_SPEC_1S: BarSpecification = BarSpecification {
     step: 1,
     aggregation: BarAggregation::Second,
@@ -141,6 +149,43 @@ pub fn parse_cfi_iso10926(value: &str) -> Result<(Option<AssetClass>, Option<Ins
     Ok((asset_class, instrument_class))
 }
 
+/// Decodes the Nautilus price precision and price increment for the given Databento
+/// fixed-precision (1e-9) `min_price_increment`.
+///
+/// The precision is the number of decimal places of the increment (e.g. 2 for a 0.25 tick and
+/// 6 for a 1/64 tick), so prices on non-standard tick sizes are not truncated. An undefined
+/// increment falls back to the `currency` precision and its smallest increment, with a warning.
+pub fn decode_price_increment(value: i64, currency: Currency) -> Result<(u8, Price)> {
+    match value {
+        0 | i64::MAX => {
+            tracing::warn!(
+                "Undefined `min_price_increment`, using {} precision {}",
+                currency.code,
+                currency.precision
+            );
+            Ok((
+                currency.precision,
+                decode_min_price_increment(value, currency)?,
+            ))
+        }
+        _ => {
+            let precision = increment_precision(value);
+            Ok((precision, Price::from_raw(value, precision)?))
+        }
+    }
+}
+
+/// Returns the number of significant decimal places of the given fixed-precision `value`.
+fn increment_precision(value: i64) -> u8 {
+    let mut value = value.unsigned_abs();
+    let mut precision = FIXED_PRECISION;
+    while precision > 0 && value % 10 == 0 {
+        value /= 10;
+        precision -= 1;
+    }
+    precision
+}
+
 pub fn decode_min_price_increment(value: i64, currency: Currency) -> Result<Price> {
     match value {
         0 | i64::MAX => Price::new(
@@ -151,6 +196,68 @@ pub fn decode_min_price_increment(value: i64, currency: Currency) -> Result<Pric
     }
 }
 
+/// Applies a Databento `display_factor` to the given fixed-precision price `value`.
+///
+/// The display factor is itself fixed-precision (units of 1e-9), an undefined or unit
+/// factor leaves the value unchanged, as do undefined prices.
+///
+/// # Errors
+///
+/// Returns an error if the adjusted value overflows an `i64`.
+pub fn apply_display_factor(value: i64, display_factor: i64) -> Result<i64> {
+    let scale = FIXED_SCALAR as i64;
+    if value == dbn::UNDEF_PRICE
+        || display_factor <= 0
+        || display_factor == i64::MAX
+        || display_factor == scale
+    {
+        return Ok(value);
+    }
+    let adjusted = i128::from(value) * i128::from(display_factor) / i128::from(scale);
+    i64::try_from(adjusted).map_err(|_| {
+        anyhow!("Value {value} with `display_factor` {display_factor} overflows a price")
+    })
+}
+
+/// Decodes a Databento fixed-precision (1e-9) `raw` price into a `Price`, rounded half away
+/// from zero to the given `precision`, or `None` for the `UNDEF_PRICE` sentinel.
+///
+/// # Errors
+///
+/// Returns an error if `precision` is invalid.
+pub fn decode_price(raw: i64, precision: u8) -> Result<Option<Price>> {
+    if raw == dbn::UNDEF_PRICE {
+        return Ok(None);
+    }
+    check_fixed_precision(precision)?;
+
+    let increment = 10_i128.pow(u32::from(FIXED_PRECISION - precision));
+    let raw = i128::from(raw);
+    let half = if raw < 0 {
+        -increment / 2
+    } else {
+        increment / 2
+    };
+    let rounded = (raw + half) / increment * increment;
+    Ok(Some(Price::from_raw(i64::try_from(rounded)?, precision)?))
+}
+
+/// Decodes a Databento size into a `Quantity` of whole units, or `None` for the
+/// `UNDEF_ORDER_SIZE` sentinel.
+///
+/// # Errors
+///
+/// Returns an error if the quantity is invalid.
+pub fn decode_quantity(raw: u32) -> Result<Option<Quantity>> {
+    if raw == dbn::UNDEF_ORDER_SIZE {
+        return Ok(None);
+    }
+    Ok(Some(Quantity::from_raw(
+        u64::from(raw) * FIXED_SCALAR as u64,
+        0,
+    )?))
+}
+
 /// # Safety
 ///
 /// - Assumes `ptr` is a valid C string pointer.
@@ -176,13 +283,18 @@ pub fn decode_equity_v1(
 ) -> Result<Equity> {
     let currency = Currency::USD(); // TODO: Temporary hard coding of US equities for now
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     Equity::new(
         instrument_id,
         instrument_id.symbol,
         None, // No ISIN available yet
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Some(Quantity::new(msg.min_lot_size_round_lot.into(), 0)?),
         None,        // TBD
         None,        // TBD
@@ -203,6 +315,11 @@ pub fn decode_futures_contract_v1(
     let underlying = unsafe { raw_ptr_to_ustr(msg.asset.as_ptr())? };
     let (asset_class, _) = parse_cfi_iso10926(&cfi_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     FuturesContract::new(
         instrument_id,
         instrument_id.symbol,
@@ -211,8 +328,8 @@ pub fn decode_futures_contract_v1(
         msg.activation,
         msg.expiration,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -235,6 +352,11 @@ pub fn decode_futures_spread_v1(
     let strategy_type = unsafe { raw_ptr_to_ustr(msg.secsubtype.as_ptr())? };
     let (asset_class, _) = parse_cfi_iso10926(&cfi_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     FuturesSpread::new(
         instrument_id,
         instrument_id.symbol,
@@ -244,8 +366,8 @@ pub fn decode_futures_spread_v1(
         msg.activation,
         msg.expiration,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -274,6 +396,11 @@ pub fn decode_options_contract_v1(
     let underlying = unsafe { raw_ptr_to_ustr(msg.underlying.as_ptr())? };
     let currency = Currency::from_str(&currency_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     OptionsContract::new(
         instrument_id,
         instrument_id.symbol,
@@ -282,10 +409,13 @@ pub fn decode_options_contract_v1(
         parse_option_kind(msg.instrument_class)?,
         msg.activation,
         msg.expiration,
-        Price::from_raw(msg.strike_price, currency.precision)?,
+        Price::from_raw(
+            apply_display_factor(msg.strike_price, msg.display_factor)?,
+            currency.precision,
+        )?,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -315,6 +445,11 @@ pub fn decode_options_spread_v1(
     let strategy_type = unsafe { raw_ptr_to_ustr(msg.secsubtype.as_ptr())? };
     let currency = Currency::from_str(&currency_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     OptionsSpread::new(
         instrument_id,
         instrument_id.symbol,
@@ -324,8 +459,8 @@ pub fn decode_options_spread_v1(
         msg.activation,
         msg.expiration,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -684,6 +819,39 @@ pub fn decode_instrument_def_msg_v1(
     }
 }
 
+/// Decodes the given Databento instrument definition into a Nautilus instrument, resolving
+/// its `InstrumentId` from the record.
+///
+/// The venue is taken from the definitions `exchange` field when it is a known ISO 10383 MIC,
+/// otherwise (such as when the field is blank) it is resolved through the `publisher_venue_map`.
+pub fn decode_instrument_def(
+    msg: &dbn::InstrumentDefMsg,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    ts_init: UnixNanos,
+) -> Result<Box<dyn Instrument>> {
+    let raw_symbol = unsafe { raw_ptr_to_ustr(msg.raw_symbol.as_ptr())? };
+    let symbol = Symbol { value: raw_symbol };
+
+    let publisher_id = msg.hd.publisher_id;
+    let exchange = msg.exchange()?.trim();
+    let venue = match Venue::new(exchange) {
+        Ok(venue) if venue.is_mic() => venue,
+        _ => {
+            if !exchange.is_empty() {
+                tracing::warn!(
+                    "Invalid `exchange` '{exchange}' for {symbol}, not a known ISO 10383 MIC"
+                );
+            }
+            *publisher_venue_map
+                .get(&publisher_id)
+                .ok_or_else(|| anyhow!("No venue found for `publisher_id` {publisher_id}"))?
+        }
+    };
+    let instrument_id = InstrumentId::new(symbol, venue);
+
+    decode_instrument_def_msg(msg, instrument_id, ts_init)
+}
+
 pub fn decode_instrument_def_msg(
     msg: &dbn::InstrumentDefMsg,
     instrument_id: InstrumentId,
@@ -727,13 +895,18 @@ pub fn decode_equity(
 ) -> Result<Equity> {
     let currency = Currency::USD(); // TODO: Temporary hard coding of US equities for now
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     Equity::new(
         instrument_id,
         instrument_id.symbol,
         None, // No ISIN available yet
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Some(Quantity::new(msg.min_lot_size_round_lot.into(), 0)?),
         None,        // TBD
         None,        // TBD
@@ -754,6 +927,11 @@ pub fn decode_futures_contract(
     let underlying = unsafe { raw_ptr_to_ustr(msg.asset.as_ptr())? };
     let (asset_class, _) = parse_cfi_iso10926(&cfi_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     FuturesContract::new(
         instrument_id,
         instrument_id.symbol,
@@ -762,8 +940,8 @@ pub fn decode_futures_contract(
         msg.activation,
         msg.expiration,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -786,6 +964,11 @@ pub fn decode_futures_spread(
     let strategy_type = unsafe { raw_ptr_to_ustr(msg.secsubtype.as_ptr())? };
     let (asset_class, _) = parse_cfi_iso10926(&cfi_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     FuturesSpread::new(
         instrument_id,
         instrument_id.symbol,
@@ -795,8 +978,8 @@ pub fn decode_futures_spread(
         msg.activation,
         msg.expiration,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -825,6 +1008,11 @@ pub fn decode_options_contract(
     let underlying = unsafe { raw_ptr_to_ustr(msg.underlying.as_ptr())? };
     let currency = Currency::from_str(&currency_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     OptionsContract::new(
         instrument_id,
         instrument_id.symbol,
@@ -833,10 +1021,13 @@ pub fn decode_options_contract(
         parse_option_kind(msg.instrument_class)?,
         msg.activation,
         msg.expiration,
-        Price::from_raw(msg.strike_price, currency.precision)?,
+        Price::from_raw(
+            apply_display_factor(msg.strike_price, msg.display_factor)?,
+            currency.precision,
+        )?,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -866,6 +1057,11 @@ pub fn decode_options_spread(
     let strategy_type = unsafe { raw_ptr_to_ustr(msg.secsubtype.as_ptr())? };
     let currency = Currency::from_str(&currency_str)?;
 
+    let (price_precision, price_increment) = decode_price_increment(
+        apply_display_factor(msg.min_price_increment, msg.display_factor)?,
+        currency,
+    )?;
+
     OptionsSpread::new(
         instrument_id,
         instrument_id.symbol,
@@ -875,8 +1071,8 @@ pub fn decode_options_spread(
         msg.activation,
         msg.expiration,
         currency,
-        currency.precision,
-        decode_min_price_increment(msg.min_price_increment, currency)?,
+        price_precision,
+        price_increment,
         Quantity::new(1.0, 0)?, // TBD
         Quantity::new(1.0, 0)?, // TBD
         None,                   // TBD
@@ -887,3 +1083,409 @@ pub fn decode_options_spread(
         ts_init,
     )
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use dbn::RecordHeader;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::databento::stubs::TS_2024_01_02;
+
+    fn str_to_c_chars<const N: usize>(s: &str) -> [c_char; N] {
+        let mut chars = [0 as c_char; N];
+        for (i, byte) in s.bytes().enumerate() {
+            chars[i] = byte as c_char;
+        }
+        chars
+    }
+
+    fn instrument_def_msg(
+        publisher_id: PublisherId,
+        raw_symbol: &str,
+        exchange: &str,
+        instrument_class: char,
+    ) -> dbn::InstrumentDefMsg {
+        dbn::InstrumentDefMsg {
+            hd: RecordHeader::new::<dbn::InstrumentDefMsg>(
+                dbn::rtype::INSTRUMENT_DEF,
+                publisher_id,
+                1,
+                TS_2024_01_02,
+            ),
+            ts_recv: TS_2024_01_02,
+            raw_symbol: str_to_c_chars(raw_symbol),
+            exchange: str_to_c_chars(exchange),
+            asset: str_to_c_chars("ES"),
+            cfi: str_to_c_chars("FFIXSX"),
+            currency: str_to_c_chars("USD"),
+            instrument_class: instrument_class as c_char,
+            min_price_increment: 250_000_000,
+            display_factor: 1_000_000_000,
+            min_lot_size_round_lot: 100,
+            activation: 1_687_470_000_000_000_000,
+            expiration: 1_710_509_400_000_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn instrument_def_msg_v1(
+        raw_symbol: &str,
+        instrument_class: char,
+    ) -> dbn::compat::InstrumentDefMsgV1 {
+        dbn::compat::InstrumentDefMsgV1 {
+            hd: RecordHeader::new::<dbn::compat::InstrumentDefMsgV1>(
+                dbn::rtype::INSTRUMENT_DEF,
+                1,
+                1,
+                TS_2024_01_02,
+            ),
+            ts_recv: TS_2024_01_02,
+            raw_symbol: str_to_c_chars(raw_symbol),
+            exchange: str_to_c_chars("XCME"),
+            asset: str_to_c_chars("ES"),
+            cfi: str_to_c_chars("FFIXSX"),
+            currency: str_to_c_chars("USD"),
+            instrument_class: instrument_class as c_char,
+            min_price_increment: 250_000_000,
+            display_factor: 1_000_000_000,
+            min_lot_size_round_lot: 100,
+            activation: 1_687_470_000_000_000_000,
+            expiration: 1_710_509_400_000_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn publisher_venue_map() -> IndexMap<PublisherId, Venue> {
+        IndexMap::from([(1, Venue::GLBX()), (2, Venue::from("XNAS"))])
+    }
+
+    #[rstest]
+    #[case(250_000_000, 1_000_000_000, 250_000_000)]
+    #[case(250_000_000, 10_000_000, 2_500_000)]
+    #[case(dbn::UNDEF_PRICE, 10_000_000, dbn::UNDEF_PRICE)]
+    #[case(250_000_000, i64::MAX, 250_000_000)]
+    fn test_apply_display_factor(
+        #[case] value: i64,
+        #[case] display_factor: i64,
+        #[case] expected: i64,
+    ) {
+        assert_eq!(
+            apply_display_factor(value, display_factor).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_apply_display_factor_overflow_returns_err() {
+        assert!(apply_display_factor(i64::MAX - 1, 2_000_000_000).is_err());
+    }
+
+    #[rstest]
+    #[case(4_250_750_000_000, 2, 4_250_750_000_000)]
+    #[case(4_250_755_000_000, 2, 4_250_760_000_000)]
+    #[case(4_250_754_999_999, 2, 4_250_750_000_000)]
+    #[case(-1_255_000_000, 2, -1_260_000_000)]
+    #[case(1_234_567_891, 9, 1_234_567_891)]
+    fn test_decode_price(#[case] raw: i64, #[case] precision: u8, #[case] expected: i64) {
+        let price = decode_price(raw, precision).unwrap().unwrap();
+
+        assert_eq!(price.raw, expected);
+        assert_eq!(price.precision, precision);
+    }
+
+    #[rstest]
+    fn test_decode_price_undef_returns_none() {
+        assert_eq!(decode_price(dbn::UNDEF_PRICE, 2).unwrap(), None);
+    }
+
+    #[rstest]
+    fn test_decode_price_invalid_precision_returns_err() {
+        assert!(decode_price(1_000_000_000, 10).is_err());
+    }
+
+    #[rstest]
+    fn test_decode_quantity() {
+        let quantity = decode_quantity(5).unwrap();
+
+        assert_eq!(quantity, Some(Quantity::from(5)));
+        assert_eq!(decode_quantity(dbn::UNDEF_ORDER_SIZE).unwrap(), None);
+    }
+
+    #[rstest]
+    fn test_decode_trade_msg_keeps_raw_price() {
+        let msg = dbn::TradeMsg {
+            hd: RecordHeader::new::<dbn::TradeMsg>(dbn::rtype::MBP_0, 1, 1, 1),
+            price: 110_015_625_000, // ZN 110.015625
+            size: 5,
+            ..Default::default()
+        };
+
+        let trade = decode_trade_msg(&msg, InstrumentId::from("ZNH4.GLBX"), 2, 0).unwrap();
+
+        assert_eq!(trade.price.raw, 110_015_625_000);
+        assert_eq!(trade.price.precision, 2);
+    }
+
+    #[rstest]
+    fn test_decode_record_trade_with_undef_price() {
+        let msg = dbn::TradeMsg {
+            hd: RecordHeader::new::<dbn::TradeMsg>(dbn::rtype::MBP_0, 1, 1, 1),
+            price: dbn::UNDEF_PRICE,
+            size: 5,
+            ..Default::default()
+        };
+
+        let (data, _) = decode_record(
+            &dbn::RecordRef::from(&msg),
+            InstrumentId::from("ESH4.GLBX"),
+            2,
+            None,
+            false,
+        )
+        .unwrap();
+
+        let Some(Data::Trade(trade)) = data else {
+            panic!("expected a trade, was {data:?}");
+        };
+        assert_eq!(trade.price.raw, dbn::UNDEF_PRICE);
+        assert_eq!(trade.size, Quantity::from(5));
+    }
+
+    #[rstest]
+    fn test_decode_record_mbp1_with_undef_level_size() {
+        let mut msg = dbn::Mbp1Msg {
+            hd: RecordHeader::new::<dbn::Mbp1Msg>(dbn::rtype::MBP_1, 1, 1, 1),
+            action: 'A' as c_char,
+            ..Default::default()
+        };
+        msg.levels[0].bid_px = 4_250_750_000_000;
+        msg.levels[0].ask_px = 4_251_000_000_000;
+        msg.levels[0].bid_sz = 10;
+        msg.levels[0].ask_sz = dbn::UNDEF_ORDER_SIZE;
+
+        let (data, _) = decode_record(
+            &dbn::RecordRef::from(&msg),
+            InstrumentId::from("ESH4.GLBX"),
+            2,
+            None,
+            false,
+        )
+        .unwrap();
+
+        let Some(Data::Quote(quote)) = data else {
+            panic!("expected a quote, was {data:?}");
+        };
+        assert_eq!(quote.bid_price, Price::from("4250.75"));
+        assert_eq!(quote.bid_size, Quantity::from(10));
+        assert_eq!(
+            quote.ask_size.raw,
+            u64::from(dbn::UNDEF_ORDER_SIZE) * FIXED_SCALAR as u64
+        );
+    }
+
+    #[rstest]
+    fn test_decode_record_mbp10_with_undef_levels() {
+        let msg = dbn::Mbp10Msg {
+            hd: RecordHeader::new::<dbn::Mbp10Msg>(dbn::rtype::MBP_10, 1, 1, 1),
+            levels: [dbn::BidAskPair {
+                bid_px: dbn::UNDEF_PRICE,
+                ask_px: dbn::UNDEF_PRICE,
+                bid_sz: dbn::UNDEF_ORDER_SIZE,
+                ask_sz: dbn::UNDEF_ORDER_SIZE,
+                ..Default::default()
+            }; 10],
+            ..Default::default()
+        };
+
+        let (data, _) = decode_record(
+            &dbn::RecordRef::from(&msg),
+            InstrumentId::from("ESH4.GLBX"),
+            2,
+            None,
+            false,
+        )
+        .unwrap();
+
+        let Some(Data::Depth10(depth)) = data else {
+            panic!("expected a depth, was {data:?}");
+        };
+        assert_eq!(depth.bids[0].price.raw, dbn::UNDEF_PRICE);
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_futures_contract() {
+        let msg = instrument_def_msg(1, "ESH4", "XCME", 'F');
+
+        let instrument = decode_instrument_def(&msg, &publisher_venue_map(), 0).unwrap();
+
+        assert_eq!(instrument.id(), InstrumentId::from("ESH4.XCME"));
+        assert_eq!(instrument.instrument_class(), InstrumentClass::Future);
+        assert_eq!(instrument.price_increment(), Price::from("0.25"));
+        assert_eq!(instrument.quote_currency(), Currency::USD());
+    }
+
+    #[rstest]
+    #[case(250_000_000, 2)] // ES 0.25
+    #[case(5_000_000, 3)] // ZN half-tick band 0.005
+    #[case(15_625_000, 6)] // ZB 1/64
+    #[case(1_000_000_000, 0)]
+    #[case(1, 9)]
+    fn test_decode_price_increment(#[case] value: i64, #[case] expected_precision: u8) {
+        let (precision, increment) = decode_price_increment(value, Currency::USD()).unwrap();
+
+        assert_eq!(precision, expected_precision);
+        assert_eq!(increment.raw, value);
+        assert_eq!(increment.precision, expected_precision);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(dbn::UNDEF_PRICE)]
+    fn test_decode_price_increment_undef_uses_currency_precision(#[case] value: i64) {
+        let (precision, increment) = decode_price_increment(value, Currency::USD()).unwrap();
+
+        assert_eq!(precision, Currency::USD().precision);
+        assert_eq!(increment, Price::from("0.01"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_futures_contract_non_standard_tick() {
+        let mut msg = instrument_def_msg(1, "ZBH4", "XCBT", 'F');
+        msg.min_price_increment = 15_625_000;
+
+        let instrument = decode_instrument_def(&msg, &publisher_venue_map(), 0).unwrap();
+
+        assert_eq!(instrument.price_precision(), 6);
+        assert_eq!(instrument.price_increment(), Price::from("0.015625"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_equity() {
+        let msg = instrument_def_msg(2, "AAPL", "XNAS", 'K');
+
+        let instrument = decode_instrument_def(&msg, &publisher_venue_map(), 0).unwrap();
+
+        assert_eq!(instrument.id(), InstrumentId::from("AAPL.XNAS"));
+        assert_eq!(instrument.instrument_class(), InstrumentClass::Spot);
+        assert_eq!(instrument.lot_size(), Some(Quantity::from(100)));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_uses_exchange_venue() {
+        let msg = instrument_def_msg(2, "SPY", "ARCX", 'K');
+
+        let instrument = decode_instrument_def(&msg, &publisher_venue_map(), 0).unwrap();
+
+        assert_eq!(instrument.id(), InstrumentId::from("SPY.ARCX"));
+    }
+
+    #[rstest]
+    #[case(1, "ESH4", "", 'F', "ESH4.GLBX")]
+    #[case(2, "AAPL", "", 'K', "AAPL.XNAS")]
+    #[case(2, "AAPL", "XXNS", 'K', "AAPL.XNAS")]
+    fn test_decode_instrument_def_blank_or_invalid_exchange_falls_back(
+        #[case] publisher_id: PublisherId,
+        #[case] raw_symbol: &str,
+        #[case] exchange: &str,
+        #[case] instrument_class: char,
+        #[case] expected: &str,
+    ) {
+        let msg = instrument_def_msg(publisher_id, raw_symbol, exchange, instrument_class);
+
+        let instrument = decode_instrument_def(&msg, &publisher_venue_map(), 0).unwrap();
+
+        assert_eq!(instrument.id(), InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_perpetual_with_undef_expiration() {
+        // A perpetual future has an activation but no expiration
+        let mut msg = instrument_def_msg(1, "BTCPERP", "XCME", 'F');
+        msg.asset = str_to_c_chars("BTC");
+        msg.cfi = str_to_c_chars("FFICSX");
+        msg.min_price_increment = 5_000_000_000;
+        msg.expiration = dbn::UNDEF_TIMESTAMP;
+
+        let instrument = decode_instrument_def(&msg, &publisher_venue_map(), 0).unwrap();
+
+        assert_eq!(instrument.id(), InstrumentId::from("BTCPERP.XCME"));
+        assert_eq!(instrument.instrument_class(), InstrumentClass::Future);
+        assert_eq!(instrument.price_increment(), Price::from("5"));
+        let future = instrument
+            .as_any()
+            .downcast_ref::<FuturesContract>()
+            .unwrap();
+        assert_eq!(future.underlying, Ustr::from("BTC"));
+        assert_eq!(future.activation_ns, 1_687_470_000_000_000_000);
+        assert_eq!(future.expiration_ns, dbn::UNDEF_TIMESTAMP);
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_msg_applies_display_factor_to_price_increment() {
+        let mut msg = instrument_def_msg(1, "ESH4", "XCME", 'F');
+        msg.display_factor = 10_000_000;
+
+        let instrument =
+            decode_instrument_def_msg(&msg, InstrumentId::from("ESH4.XCME"), 0).unwrap();
+
+        assert_eq!(instrument.price_precision(), 4);
+        assert_eq!(instrument.price_increment(), Price::from("0.0025"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_msg_applies_display_factor_to_strike_price() {
+        let mut msg = instrument_def_msg(1, "ESH4 C4800", "XCME", 'C');
+        msg.strike_price = 480_000_000_000_000;
+        msg.display_factor = 10_000_000;
+
+        let instrument =
+            decode_instrument_def_msg(&msg, InstrumentId::from("ESH4 C4800.XCME"), 0).unwrap();
+
+        let option = instrument
+            .as_any()
+            .downcast_ref::<OptionsContract>()
+            .unwrap();
+        assert_eq!(option.strike_price, Price::from("4800.00"));
+        assert_eq!(instrument.price_increment(), Price::from("0.0025"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_msg_v1_applies_display_factor() {
+        let mut future_msg = instrument_def_msg_v1("ESH4", 'F');
+        future_msg.display_factor = 10_000_000;
+        let mut option_msg = instrument_def_msg_v1("ESH4 C4800", 'C');
+        option_msg.strike_price = 480_000_000_000_000;
+        option_msg.display_factor = 10_000_000;
+
+        let future =
+            decode_instrument_def_msg_v1(&future_msg, InstrumentId::from("ESH4.XCME"), 0).unwrap();
+        let option =
+            decode_instrument_def_msg_v1(&option_msg, InstrumentId::from("ESH4 C4800.XCME"), 0)
+                .unwrap();
+
+        assert_eq!(future.price_increment(), Price::from("0.0025"));
+        assert_eq!(
+            option
+                .as_any()
+                .downcast_ref::<OptionsContract>()
+                .unwrap()
+                .strike_price,
+            Price::from("4800.00")
+        );
+    }
+
+    #[rstest]
+    fn test_decode_instrument_def_msg_display_factor_overflow_returns_err() {
+        let mut msg = instrument_def_msg(1, "ESH4", "XCME", 'F');
+        msg.min_price_increment = i64::MAX - 1;
+        msg.display_factor = 2_000_000_000;
+
+        let result = decode_instrument_def_msg(&msg, InstrumentId::from("ESH4.XCME"), 0);
+
+        assert!(result.is_err());
+    }
+}
@@ -35,6 +35,7 @@ use ustr::Ustr;
 use super::{
     decode::{decode_instrument_def_msg_v1, decode_record, raw_ptr_to_ustr},
     types::{DatabentoPublisher, Dataset, PublisherId},
+    venues::{normalize_glbx_exchange_map, normalize_venue},
 };
 
 /// Provides a Nautilus data loader for Databento Binary Encoding (DBN) format data.
@@ -133,7 +134,12 @@ impl DatabentoDataLoader {
 
         self.publisher_venue_map = publishers
             .into_iter()
-            .map(|p| (p.publisher_id, Venue::from(p.venue.as_str())))
+            .map(|p| {
+                (
+                    p.publisher_id,
+                    normalize_venue(Venue::from(p.venue.as_str())),
+                )
+            })
             .collect::<IndexMap<u16, Venue>>();
 
         Ok(())
@@ -141,7 +147,7 @@ impl DatabentoDataLoader {
 
     // Return the map of CME Globex symbols to exchange venues.
     pub fn load_glbx_exchange_map(&mut self, map: HashMap<Symbol, Venue>) {
-        self.glbx_exchange_map = map;
+        self.glbx_exchange_map = normalize_glbx_exchange_map(map);
     }
 
     /// Return the internal Databento publishers currently held by the loader.
@@ -16,8 +16,12 @@
 pub mod common;
 pub mod decode;
 pub mod loader;
+pub mod stream;
+#[cfg(any(test, feature = "stubs"))]
+pub mod stubs;
 pub mod symbology;
 pub mod types;
+pub mod venues;
 
 #[cfg(feature = "python")]
 pub mod python;
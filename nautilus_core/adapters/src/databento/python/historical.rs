@@ -16,7 +16,6 @@
 use std::{collections::HashMap, fs, num::NonZeroU64, sync::Arc};
 
 use databento::historical::timeseries::GetRangeParams;
-use dbn::Publisher;
 use indexmap::IndexMap;
 use nautilus_core::{
     python::to_pyvalue_err,
@@ -25,7 +24,7 @@ use nautilus_core::{
 use nautilus_model::{
     data::{bar::Bar, quote::QuoteTick, trade::TradeTick, Data},
     enums::BarAggregation,
-    identifiers::{instrument_id::InstrumentId, symbol::Symbol, venue::Venue},
+    identifiers::{symbol::Symbol, venue::Venue},
 };
 use pyo3::{
     exceptions::PyException,
@@ -37,7 +36,7 @@ use tokio::sync::Mutex;
 use super::loader::convert_instrument_to_pyobject;
 use crate::databento::{
     common::get_date_time_range,
-    decode::{decode_instrument_def_msg, decode_record, raw_ptr_to_ustr},
+    decode::{decode_instrument_def, decode_record},
     symbology::decode_nautilus_instrument_id,
     types::{DatabentoPublisher, PublisherId},
 };
@@ -151,24 +150,7 @@ impl DatabentoHistoricalClient {
             let mut instruments = Vec::new();
 
             while let Ok(Some(msg)) = decoder.decode_record::<dbn::InstrumentDefMsg>().await {
-                let raw_symbol = unsafe { raw_ptr_to_ustr(msg.raw_symbol.as_ptr()).unwrap() };
-                let symbol = Symbol { value: raw_symbol };
-
-                let publisher = msg.hd.publisher().expect("Invalid `publisher` for record");
-                let venue = match publisher {
-                    Publisher::GlbxMdp3Glbx => {
-                        // SAFETY: GLBX instruments have a valid `exchange` field
-                        let exchange = msg.exchange().unwrap();
-                        Venue::from_code(exchange)
-                            .unwrap_or_else(|_| panic!("`Venue` not found for exchange {exchange}"))
-                    }
-                    _ => *publisher_venue_map
-                        .get(&msg.hd.publisher_id)
-                        .unwrap_or_else(|| panic!("`Venue` not found for `publisher` {publisher}")),
-                };
-                let instrument_id = InstrumentId::new(symbol, venue);
-
-                let result = decode_instrument_def_msg(msg, instrument_id, ts_init);
+                let result = decode_instrument_def(msg, &publisher_venue_map, ts_init);
                 match result {
                     Ok(instrument) => instruments.push(instrument),
                     Err(e) => eprintln!("{e:?}"),
@@ -0,0 +1,428 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead},
+    marker::PhantomData,
+};
+
+use dbn::{
+    decode::{dbn::Decoder, DbnMetadata, DecodeRecord, DynReader},
+    Compression, Record,
+};
+use futures::{future, Stream, StreamExt};
+use indexmap::IndexMap;
+use nautilus_model::identifiers::{instrument_id::InstrumentId, symbol::Symbol, venue::Venue};
+
+use super::{
+    symbology::{DatabentoSymbologyResolver, SymbologyError},
+    types::PublisherId,
+};
+
+/// The magic bytes at the start of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Provides an iterator over the records of a DBN stream, yielding each record along with
+/// its resolved Nautilus `InstrumentId`.
+///
+/// Records are yielded by value, as a `dbn::RecordRef` borrows the decoder's buffer and so
+/// cannot outlive a call to `next` (use `dbn::RecordRef::from(&record)` where a reference is
+/// required). Decoding and symbology errors are yielded as `Err` items (with decoding errors as
+/// `SymbologyError::Dbn`), leaving the caller to decide whether to skip them or abort. Records
+/// with an unsupported record type are omitted if the resolver is set to skip them.
+pub struct DatabentoRecordStream<R, T>
+where
+    R: io::Read,
+    T: dbn::HasRType + Clone,
+{
+    decoder: Decoder<R>,
+    resolver: DatabentoSymbologyResolver,
+    _record: PhantomData<T>,
+}
+
+impl<R, T> DatabentoRecordStream<R, T>
+where
+    R: io::Read,
+    T: dbn::HasRType + Clone,
+{
+    #[must_use]
+    pub fn new(
+        decoder: Decoder<R>,
+        publisher_venue_map: IndexMap<PublisherId, Venue>,
+        glbx_exchange_map: HashMap<Symbol, Venue>,
+    ) -> Self {
+        let metadata = decoder.metadata().clone();
+        Self {
+            decoder,
+            resolver: DatabentoSymbologyResolver::new(
+                metadata,
+                publisher_venue_map,
+                glbx_exchange_map,
+            ),
+            _record: PhantomData,
+        }
+    }
+
+    /// Return the symbology resolver for the stream.
+    pub fn resolver_mut(&mut self) -> &mut DatabentoSymbologyResolver {
+        &mut self.resolver
+    }
+
+    /// Return the number of distinct instrument IDs yielded by the stream so far.
+    #[must_use]
+    pub fn distinct_instrument_count(&self) -> usize {
+        self.resolver.distinct_instrument_count()
+    }
+
+    /// Return the distinct instrument IDs yielded by the stream so far (in arbitrary order).
+    pub fn distinct_instruments(&self) -> impl Iterator<Item = &InstrumentId> {
+        self.resolver.distinct_instruments()
+    }
+}
+
+impl<R, T> DatabentoRecordStream<DynReader<'static, io::BufReader<R>>, T>
+where
+    R: io::Read,
+    T: dbn::HasRType + Clone,
+{
+    /// Creates a new record stream from a DBN `reader` with the given `compression`, which is
+    /// decompressed incrementally as records are decoded.
+    ///
+    /// Only zstd compressed (or uncompressed) input is supported, as for `dbn::Compression`.
+    /// Gzip compressed input results in `SymbologyError::UnsupportedCompression` rather than
+    /// a decoding error. Corrupt or truncated compressed input results in an error, either here
+    /// (if the metadata cannot be decoded) or as an `Err` item of the stream.
+    pub fn from_compressed_reader(
+        reader: R,
+        compression: Compression,
+        publisher_venue_map: IndexMap<PublisherId, Venue>,
+        glbx_exchange_map: HashMap<Symbol, Venue>,
+    ) -> Result<Self, SymbologyError> {
+        let mut reader = io::BufReader::new(reader);
+        let prefix = reader
+            .fill_buf()
+            .map_err(|e| dbn::Error::io(e, "reading DBN stream prefix"))?;
+        if prefix.starts_with(&GZIP_MAGIC) {
+            return Err(SymbologyError::UnsupportedCompression {
+                compression: "gzip",
+            });
+        }
+        let decoder = Decoder::new(DynReader::with_buffer(reader, compression)?)?;
+        Ok(Self::new(decoder, publisher_venue_map, glbx_exchange_map))
+    }
+}
+
+impl<R, T> Iterator for DatabentoRecordStream<R, T>
+where
+    R: io::Read,
+    T: dbn::HasRType + Clone,
+{
+    type Item = Result<(InstrumentId, T), SymbologyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let record = match self.decoder.decode_record::<T>() {
+                Ok(Some(record)) => record.clone(),
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            let rec_ref = dbn::RecordRef::from(&record);
+            let publisher_id = rec_ref.header().publisher_id;
+            match self.resolver.try_resolve(&rec_ref, publisher_id) {
+                Ok(Some(instrument_id)) => return Some(Ok((instrument_id, record))),
+                Ok(None) => continue, // Unsupported record type being skipped
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Represents an item of an asynchronous DBN stream.
+///
+/// Live sessions send the session metadata ahead of any records, so a stream may start with a
+/// `Metadata` item, and may send it again on reconnection.
+#[derive(Clone, Debug)]
+pub enum DbnStreamItem<T> {
+    Metadata(dbn::Metadata),
+    Record(T),
+}
+
+impl DatabentoSymbologyResolver {
+    /// Resolve the records of the given asynchronous stream, yielding each record along with
+    /// its resolved Nautilus `InstrumentId`.
+    ///
+    /// Resolution is lazy, happening as the returned stream is polled, and the resolver (along
+    /// with its per-date symbol map cache) is owned by the returned stream for its lifetime.
+    /// A `Metadata` item replaces the resolver's metadata and yields nothing. Records with an
+    /// unsupported record type are omitted if the resolver is set to skip them.
+    pub fn resolve_stream<S, T>(
+        mut self,
+        stream: S,
+    ) -> impl Stream<Item = Result<(InstrumentId, T), SymbologyError>> + Send
+    where
+        S: Stream<Item = DbnStreamItem<T>> + Send,
+        T: dbn::HasRType + Send,
+    {
+        stream.filter_map(move |item| {
+            let result = match item {
+                DbnStreamItem::Metadata(metadata) => {
+                    self.set_metadata(metadata);
+                    None
+                }
+                DbnStreamItem::Record(record) => {
+                    let rec_ref = dbn::RecordRef::from(&record);
+                    let publisher_id = rec_ref.header().publisher_id;
+                    self.try_resolve(&rec_ref, publisher_id)
+                        .transpose()
+                        .map(|result| result.map(|instrument_id| (instrument_id, record)))
+                }
+            };
+            future::ready(result)
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use dbn::{
+        encode::{dbn::Encoder, EncodeRecord},
+        TradeMsg,
+    };
+    use rstest::rstest;
+
+    use super::*;
+    use crate::databento::stubs::{
+        make_metadata, make_symbol_mapping, make_trade_record, TS_2024_01_02,
+    };
+
+    fn dbn_buffer(records: &[TradeMsg]) -> Vec<u8> {
+        encode_dbn(records, Compression::None)
+    }
+
+    fn encode_dbn(records: &[TradeMsg], compression: Compression) -> Vec<u8> {
+        let metadata = make_metadata(vec![
+            make_symbol_mapping("ESH4", 5602),
+            make_symbol_mapping("NQH4", 5603),
+            make_symbol_mapping("CLH4", 5604),
+        ]);
+
+        let mut buffer = Vec::new();
+        match compression {
+            Compression::None => {
+                let mut encoder = Encoder::new(&mut buffer, &metadata).unwrap();
+                for record in records {
+                    encoder.encode_record(record).unwrap();
+                }
+            }
+            Compression::ZStd => {
+                let mut encoder = Encoder::with_zstd(&mut buffer, &metadata).unwrap();
+                for record in records {
+                    encoder.encode_record(record).unwrap();
+                }
+            }
+        }
+        buffer
+    }
+
+    #[rstest]
+    fn test_record_stream_yields_instrument_ids() {
+        let records = [
+            make_trade_record(5602, 1, TS_2024_01_02),
+            make_trade_record(5603, 1, TS_2024_01_02 + 1),
+            make_trade_record(5602, 1, TS_2024_01_02 + 2),
+        ];
+        let buffer = dbn_buffer(&records);
+        let decoder = Decoder::new(buffer.as_slice()).unwrap();
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+
+        let stream =
+            DatabentoRecordStream::<_, TradeMsg>::new(decoder, publisher_venue_map, HashMap::new());
+        let results: Vec<(InstrumentId, TradeMsg)> = stream.collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(results[1].0, InstrumentId::from("NQH4.GLBX"));
+        assert_eq!(results[2].0, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(results[2].1.ts_recv, TS_2024_01_02 + 2);
+    }
+
+    #[rstest]
+    fn test_record_stream_yields_err_and_continues() {
+        let records = [
+            make_trade_record(5602, 1, TS_2024_01_02),
+            make_trade_record(9999, 1, TS_2024_01_02), // Unmapped
+            make_trade_record(5603, 1, TS_2024_01_02),
+        ];
+        let buffer = dbn_buffer(&records);
+        let decoder = Decoder::new(buffer.as_slice()).unwrap();
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+
+        let results: Vec<Result<(InstrumentId, TradeMsg), SymbologyError>> =
+            DatabentoRecordStream::new(decoder, publisher_venue_map, HashMap::new()).collect();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(SymbologyError::MissingSymbol {
+                instrument_id: 9999,
+                ..
+            })
+        ));
+        assert_eq!(
+            results[2].as_ref().unwrap().0,
+            InstrumentId::from("NQH4.GLBX")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_stream_with_metadata_first() {
+        let metadata = make_metadata(vec![
+            make_symbol_mapping("ESH4", 5602),
+            make_symbol_mapping("NQH4", 5603),
+        ]);
+        let mut placeholder = metadata.clone();
+        placeholder.mappings.clear();
+        let resolver = DatabentoSymbologyResolver::new(
+            placeholder,
+            IndexMap::from([(1, Venue::GLBX())]),
+            HashMap::new(),
+        );
+        let items = futures::stream::iter(vec![
+            DbnStreamItem::Metadata(metadata),
+            DbnStreamItem::Record(make_trade_record(5602, 1, TS_2024_01_02)),
+            DbnStreamItem::Record(make_trade_record(5603, 1, TS_2024_01_02 + 1)),
+            DbnStreamItem::Record(make_trade_record(5602, 1, TS_2024_01_02 + 2)),
+        ]);
+
+        let results: Vec<(InstrumentId, TradeMsg)> = resolver
+            .resolve_stream(items)
+            .map(|result| result.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(results[1].0, InstrumentId::from("NQH4.GLBX"));
+        assert_eq!(results[2].0, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(results[2].1.ts_recv, TS_2024_01_02 + 2);
+    }
+
+    #[rstest]
+    fn test_record_stream_from_zstd_reader() {
+        let records = [
+            make_trade_record(5602, 1, TS_2024_01_02),
+            make_trade_record(5603, 1, TS_2024_01_02 + 1),
+        ];
+        let buffer = encode_dbn(&records, Compression::ZStd);
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+
+        let stream = DatabentoRecordStream::<_, TradeMsg>::from_compressed_reader(
+            buffer.as_slice(),
+            Compression::ZStd,
+            publisher_venue_map,
+            HashMap::new(),
+        )
+        .unwrap();
+        let instrument_ids: Vec<InstrumentId> = stream.map(|result| result.unwrap().0).collect();
+
+        assert_eq!(
+            instrument_ids,
+            vec![
+                InstrumentId::from("ESH4.GLBX"),
+                InstrumentId::from("NQH4.GLBX")
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_record_stream_from_truncated_zstd_reader_returns_err() {
+        let records: Vec<TradeMsg> = (0..100)
+            .map(|i| make_trade_record(5602, 1, TS_2024_01_02 + i))
+            .collect();
+        let buffer = encode_dbn(&records, Compression::ZStd);
+        let truncated = &buffer[..buffer.len() / 2];
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+
+        let result = DatabentoRecordStream::<_, TradeMsg>::from_compressed_reader(
+            truncated,
+            Compression::ZStd,
+            publisher_venue_map,
+            HashMap::new(),
+        )
+        .and_then(|stream| stream.collect::<Result<Vec<_>, _>>());
+
+        assert!(matches!(result, Err(SymbologyError::Dbn(_))));
+    }
+
+    #[rstest]
+    #[case(Compression::None)]
+    #[case(Compression::ZStd)]
+    fn test_record_stream_from_gzip_reader_returns_unsupported_compression(
+        #[case] compression: Compression,
+    ) {
+        // Gzip header of a file compressed with `gzip -9`
+        let buffer = [0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03];
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+
+        let result = DatabentoRecordStream::<_, TradeMsg>::from_compressed_reader(
+            buffer.as_slice(),
+            compression,
+            publisher_venue_map,
+            HashMap::new(),
+        );
+
+        let Err(e) = result else {
+            panic!("Expected gzip input to be rejected");
+        };
+        assert!(matches!(
+            e,
+            SymbologyError::UnsupportedCompression {
+                compression: "gzip"
+            }
+        ));
+        assert_eq!(e.kind(), "unsupported_compression");
+    }
+
+    #[rstest]
+    fn test_record_stream_distinct_instruments() {
+        let records = [
+            make_trade_record(5602, 1, TS_2024_01_02),
+            make_trade_record(5603, 1, TS_2024_01_02 + 1),
+            make_trade_record(5602, 1, TS_2024_01_02 + 2),
+            make_trade_record(5604, 1, TS_2024_01_02 + 3),
+            make_trade_record(5603, 1, TS_2024_01_02 + 4),
+        ];
+        let buffer = dbn_buffer(&records);
+        let decoder = Decoder::new(buffer.as_slice()).unwrap();
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+        let mut stream =
+            DatabentoRecordStream::<_, TradeMsg>::new(decoder, publisher_venue_map, HashMap::new());
+
+        assert_eq!(stream.by_ref().count(), 5);
+        assert_eq!(stream.distinct_instrument_count(), 3);
+        let mut distinct: Vec<String> = stream
+            .distinct_instruments()
+            .map(ToString::to_string)
+            .collect();
+        distinct.sort();
+        assert_eq!(distinct, vec!["CLH4.GLBX", "ESH4.GLBX", "NQH4.GLBX"]);
+    }
+}
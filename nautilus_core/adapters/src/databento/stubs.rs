@@ -0,0 +1,243 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+//! Provides builders of owned DBN records and metadata for tests.
+//!
+//! A `dbn::RecordRef` borrows its record, so borrow the returned record with
+//! `dbn::RecordRef::from(&record)` where a reference is required.
+
+use std::{ffi::c_char, num::NonZeroU64};
+
+use dbn::{
+    ImbalanceMsg, InstrumentDefMsg, MappingInterval, Metadata, OhlcvMsg, RecordHeader, SType,
+    Schema, StatMsg, StatusMsg, SymbolMapping, TradeMsg,
+};
+use indexmap::IndexMap;
+use nautilus_model::identifiers::venue::Venue;
+use time::{Date, Month};
+
+use super::types::PublisherId;
+
+/// The UNIX nanoseconds of 2024-01-02T00:00:00Z, a date within the stub metadata query range.
+pub const TS_2024_01_02: u64 = 1_704_153_600_000_000_000;
+
+/// Returns the given `day` of January 2024.
+#[must_use]
+pub fn make_date(day: u8) -> Date {
+    Date::from_calendar_date(2024, Month::January, day).unwrap()
+}
+
+/// Returns a map of the `GLBX.MDP3` publisher (1) to the `GLBX` venue.
+#[must_use]
+pub fn make_publisher_venue_map() -> IndexMap<PublisherId, Venue> {
+    IndexMap::from([(1, Venue::GLBX())])
+}
+
+/// Returns a symbol mapping of the given `raw_symbol` to the Databento `instrument_id` for
+/// the whole stub metadata query range (see `make_metadata`).
+#[must_use]
+pub fn make_symbol_mapping(raw_symbol: &str, instrument_id: u32) -> SymbolMapping {
+    SymbolMapping {
+        raw_symbol: raw_symbol.to_string(),
+        intervals: vec![MappingInterval {
+            start_date: Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            end_date: Date::from_calendar_date(2024, Month::January, 4).unwrap(),
+            symbol: instrument_id.to_string(),
+        }],
+    }
+}
+
+/// Returns the metadata of a `GLBX.MDP3` trades query from 2024-01-01 to 2024-01-04
+/// (exclusive), with the given raw symbol to Databento `instrument_id` `mappings`.
+#[must_use]
+pub fn make_metadata(mappings: Vec<SymbolMapping>) -> Metadata {
+    Metadata::builder()
+        .dataset("GLBX.MDP3".to_string())
+        .schema(Some(Schema::Trades))
+        .start(1_704_067_200_000_000_000) // 2024-01-01T00:00:00Z
+        .end(NonZeroU64::new(1_704_326_400_000_000_000)) // 2024-01-04T00:00:00Z
+        .stype_in(Some(SType::RawSymbol))
+        .stype_out(SType::InstrumentId)
+        .mappings(mappings)
+        .build()
+}
+
+/// Returns the stub metadata with `ESH4` mapped to the Databento `instrument_id` 5602.
+#[must_use]
+pub fn make_esh4_metadata() -> Metadata {
+    make_metadata(vec![make_symbol_mapping("ESH4", 5602)])
+}
+
+/// Returns the stub metadata with the share class symbols `BRK.B`, `BRK/B`, `BRK B` and `BRKB`
+/// mapped to the Databento instrument IDs 1 to 4.
+#[must_use]
+pub fn make_share_class_metadata() -> Metadata {
+    make_metadata(
+        ["BRK.B", "BRK/B", "BRK B", "BRKB"]
+            .iter()
+            .zip(1..)
+            .map(|(raw_symbol, instrument_id)| make_symbol_mapping(raw_symbol, instrument_id))
+            .collect(),
+    )
+}
+
+/// Returns a trade record for the given Databento `instrument_id`, with the `ts_recv` also
+/// used as the `ts_event`.
+#[must_use]
+pub fn make_trade_record(instrument_id: u32, publisher_id: PublisherId, ts_recv: u64) -> TradeMsg {
+    TradeMsg {
+        hd: RecordHeader::new::<TradeMsg>(dbn::rtype::MBP_0, publisher_id, instrument_id, ts_recv),
+        ts_recv,
+        ..Default::default()
+    }
+}
+
+/// Returns an OHLCV bar record of the given `rtype` (e.g. `dbn::rtype::OHLCV_1M`) for the
+/// given Databento `instrument_id`.
+#[must_use]
+pub fn make_ohlcv_record(
+    rtype: u8,
+    instrument_id: u32,
+    publisher_id: PublisherId,
+    ts_event: u64,
+) -> OhlcvMsg {
+    OhlcvMsg {
+        hd: RecordHeader::new::<OhlcvMsg>(rtype, publisher_id, instrument_id, ts_event),
+        ..Default::default()
+    }
+}
+
+/// Returns a statistics record for the given Databento `instrument_id`.
+#[must_use]
+pub fn make_stat_record(
+    instrument_id: u32,
+    publisher_id: PublisherId,
+    ts_event: u64,
+    ts_recv: u64,
+) -> StatMsg {
+    StatMsg {
+        hd: RecordHeader::new::<StatMsg>(
+            dbn::rtype::STATISTICS,
+            publisher_id,
+            instrument_id,
+            ts_event,
+        ),
+        ts_recv,
+        ..Default::default()
+    }
+}
+
+/// Returns an imbalance record for the given Databento `instrument_id`, with the `ts_recv`
+/// also used as the `ts_event`.
+#[must_use]
+pub fn make_imbalance_record(
+    instrument_id: u32,
+    publisher_id: PublisherId,
+    ts_recv: u64,
+) -> ImbalanceMsg {
+    ImbalanceMsg {
+        hd: RecordHeader::new::<ImbalanceMsg>(
+            dbn::rtype::IMBALANCE,
+            publisher_id,
+            instrument_id,
+            ts_recv,
+        ),
+        ts_recv,
+        ..Default::default()
+    }
+}
+
+/// Returns a trading status record for the given Databento `instrument_id`.
+#[must_use]
+pub fn make_status_record(
+    instrument_id: u32,
+    publisher_id: PublisherId,
+    ts_event: u64,
+    ts_recv: u64,
+) -> StatusMsg {
+    StatusMsg {
+        hd: RecordHeader::new::<StatusMsg>(
+            dbn::rtype::STATUS,
+            publisher_id,
+            instrument_id,
+            ts_event,
+        ),
+        ts_recv,
+        ..Default::default()
+    }
+}
+
+/// Returns an instrument definition record for the given Databento `instrument_id`, with the
+/// given `instrument_class` code and `cfi` code (of at most six characters).
+#[must_use]
+pub fn make_definition_record(
+    instrument_id: u32,
+    publisher_id: PublisherId,
+    instrument_class: char,
+    cfi: &str,
+) -> InstrumentDefMsg {
+    let mut cfi_chars = [0 as c_char; 7];
+    for (i, c) in cfi.bytes().enumerate() {
+        cfi_chars[i] = c as c_char;
+    }
+    InstrumentDefMsg {
+        hd: RecordHeader::new::<InstrumentDefMsg>(
+            dbn::rtype::INSTRUMENT_DEF,
+            publisher_id,
+            instrument_id,
+            TS_2024_01_02,
+        ),
+        ts_recv: TS_2024_01_02,
+        cfi: cfi_chars,
+        instrument_class: instrument_class as c_char,
+        ..Default::default()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use indexmap::IndexMap;
+    use nautilus_model::identifiers::{instrument_id::InstrumentId, venue::Venue};
+    use rstest::rstest;
+
+    use super::*;
+    use crate::databento::symbology::decode_nautilus_instrument_id;
+
+    #[rstest]
+    fn test_stub_records_resolve() {
+        let metadata = make_metadata(vec![make_symbol_mapping("ESH4", 5602)]);
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+        let bar = make_ohlcv_record(dbn::rtype::OHLCV_1M, 5602, 1, TS_2024_01_02);
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX())]);
+
+        for rec_ref in [dbn::RecordRef::from(&trade), dbn::RecordRef::from(&bar)] {
+            let instrument_id = decode_nautilus_instrument_id(
+                &rec_ref,
+                1,
+                &metadata,
+                &publisher_venue_map,
+                &HashMap::new(),
+            )
+            .unwrap();
+
+            assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        }
+    }
+}
@@ -0,0 +1,1915 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap},
+    ffi::c_char,
+};
+
+use databento::dbn::{PitSymbolMap, Record};
+use indexmap::IndexMap;
+use nautilus_model::{
+    enums::{AssetClass, InstrumentClass},
+    identifiers::{instrument_id::InstrumentId, symbol::Symbol, venue::Venue},
+};
+use ustr::Ustr;
+
+use super::{
+    build_symbol_map, clamp_to_metadata_dates, decode_instrument_id_and_ts, definition_classes,
+    is_dataset_publisher, is_glbx_publisher, raw_symbol_for_date, resolve_instrument_id,
+    resolve_symbol, resolve_symbol_venue, spread_leg_count, ts_to_date, DatabentoSymbologyResolver,
+    DateTimezone, IdFormat, ResolveOptions, ResolvedInstrument, SymbolMapProvider, SymbologyError,
+    TimestampSource, GLBX_DATASET,
+};
+use crate::databento::types::PublisherId;
+
+/// Returns the Nautilus `InstrumentId` for the given record.
+///
+/// Records from both DBN version 1 and version 2 are supported. The market data record
+/// layouts (including `TradeMsg` and `Mbp1Msg`) are identical between the versions, with the
+/// `instrument_id` and `ts_event` in the common record header, so no version specific
+/// extraction is required. Only the instrument definition, error, symbol mapping and system
+/// records changed layout, which the `dbn` decoder upgrades when decoding version 1 files.
+pub fn decode_nautilus_instrument_id(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<InstrumentId, SymbologyError> {
+    decode_resolved_instrument(
+        rec_ref,
+        publisher_id,
+        metadata,
+        publisher_venue_map,
+        glbx_exchange_map,
+        |_, _| None,
+    )
+    .map(|resolved| resolved.instrument_id)
+}
+
+/// Returns the full Databento symbology for the given record, including the raw symbol the
+/// Nautilus `InstrumentId` was resolved from.
+pub fn decode_nautilus_instrument_id_full(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<ResolvedInstrument, SymbologyError> {
+    let mut resolved = decode_resolved_instrument(
+        rec_ref,
+        publisher_id,
+        metadata,
+        publisher_venue_map,
+        glbx_exchange_map,
+        |_, _| None,
+    )?;
+    (resolved.asset_class, resolved.instrument_class) =
+        definition_classes(rec_ref, resolved.dataset);
+    Ok(resolved)
+}
+
+/// Returns the venue for the given record, resolved as for `decode_nautilus_instrument_id` but
+/// without constructing the Nautilus `InstrumentId` (such as for sharding records by venue).
+pub fn resolve_venue(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<Venue, SymbologyError> {
+    let (instrument_id, date) = instrument_id_and_date(rec_ref, metadata)?;
+    let symbol_map = build_symbol_map(metadata, date)?;
+    let raw_symbol = raw_symbol_for_date(&symbol_map, instrument_id, publisher_id, date)?;
+    let symbol = resolve_symbol(Ustr::from(raw_symbol), publisher_id, None)?;
+
+    resolve_symbol_venue(
+        &symbol,
+        Some(instrument_id),
+        publisher_id,
+        publisher_venue_map,
+        glbx_exchange_map,
+        &ResolveOptions::default(),
+    )
+}
+
+/// Returns the Nautilus `InstrumentId` for the given record with the given `raw_symbol`,
+/// rather than looking up the symbol in the DBN metadata (such as for decoding a single known
+/// record when debugging).
+///
+/// The record type is validated and the venue is resolved as for
+/// `decode_nautilus_instrument_id`, and an empty `raw_symbol` resolves to an error.
+pub fn resolve_with_symbol(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    raw_symbol: &str,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<InstrumentId, SymbologyError> {
+    // Only the record type is validated, the timestamp is not needed without a symbol map
+    let (instrument_id, _) =
+        decode_instrument_id_and_ts(rec_ref, TimestampSource::default(), 0, 0, 0)?;
+
+    resolve_instrument_id(
+        Ustr::from(raw_symbol),
+        Some(instrument_id),
+        publisher_id,
+        publisher_venue_map,
+        glbx_exchange_map,
+        &ResolveOptions::default(),
+    )
+}
+
+/// Returns the Nautilus `InstrumentId` for the given record, resolving the venue with the
+/// given `venue_resolver` first.
+///
+/// The default GLBX exchange and publisher venue resolution is only used when the
+/// `venue_resolver` returns `None`, allowing custom venue taxonomies.
+pub fn decode_nautilus_instrument_id_with(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+    venue_resolver: impl Fn(&Symbol, PublisherId) -> Option<Venue>,
+) -> Result<InstrumentId, SymbologyError> {
+    decode_resolved_instrument(
+        rec_ref,
+        publisher_id,
+        metadata,
+        publisher_venue_map,
+        glbx_exchange_map,
+        venue_resolver,
+    )
+    .map(|resolved| resolved.instrument_id)
+}
+
+/// Returns the Nautilus `InstrumentId` for the given record on the given `date`.
+///
+/// This is a fast path for when the date of the records is already known (such as when files
+/// are partitioned by day), skipping the record type dispatch and timestamp to date conversion.
+/// The Databento `instrument_id` is taken from the record header.
+pub fn decode_nautilus_instrument_id_for_date(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    date: time::Date,
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<InstrumentId, SymbologyError> {
+    resolve_for_date(
+        rec_ref.header().instrument_id,
+        date,
+        publisher_id,
+        metadata,
+        publisher_venue_map,
+        glbx_exchange_map,
+        |_, _| None,
+    )
+    .map(|resolved| resolved.instrument_id)
+}
+
+/// Returns the Nautilus `InstrumentId` for the given record, taking the raw symbol from the
+/// given `symbol_map` of Databento `instrument_id` to raw symbol rather than from DBN metadata.
+///
+/// This is for pipelines which persist their own symbol maps, so there is no per-date symbol
+/// map selection. The Databento `instrument_id` is taken from the record header, and the venue
+/// is resolved as for `decode_nautilus_instrument_id`.
+pub fn decode_nautilus_instrument_id_from_map(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    symbol_map: &HashMap<u32, Ustr>,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<InstrumentId, SymbologyError> {
+    let instrument_id = rec_ref.header().instrument_id;
+    let raw_symbol = symbol_map
+        .get(&instrument_id)
+        .ok_or(SymbologyError::MissingMappedSymbol {
+            instrument_id,
+            publisher_id,
+        })?;
+
+    resolve_instrument_id(
+        *raw_symbol,
+        Some(instrument_id),
+        publisher_id,
+        publisher_venue_map,
+        glbx_exchange_map,
+        &ResolveOptions::default(),
+    )
+}
+
+#[tracing::instrument(
+    name = "decode_nautilus_instrument_id",
+    level = "debug",
+    skip(rec_ref, metadata, publisher_venue_map, glbx_exchange_map, venue_resolver),
+    fields(rtype = rec_ref.header().rtype, date = tracing::field::Empty)
+)]
+fn decode_resolved_instrument(
+    rec_ref: &dbn::RecordRef,
+    publisher_id: PublisherId,
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+    venue_resolver: impl Fn(&Symbol, PublisherId) -> Option<Venue>,
+) -> Result<ResolvedInstrument, SymbologyError> {
+    let (instrument_id, date) = instrument_id_and_date(rec_ref, metadata)?;
+    tracing::Span::current().record("date", tracing::field::display(date));
+
+    resolve_for_date(
+        instrument_id,
+        date,
+        publisher_id,
+        metadata,
+        publisher_venue_map,
+        glbx_exchange_map,
+        venue_resolver,
+    )
+}
+
+/// Returns the Databento `instrument_id` and the symbol map date for the given record, with
+/// the default timestamp source and offsets.
+fn instrument_id_and_date(
+    rec_ref: &dbn::RecordRef,
+    metadata: &dbn::Metadata,
+) -> Result<(u32, time::Date), SymbologyError> {
+    let (instrument_id, nanoseconds) =
+        decode_instrument_id_and_ts(rec_ref, TimestampSource::default(), 0, 0, metadata.start)?;
+    let date = clamp_to_metadata_dates(
+        ts_to_date(nanoseconds, rec_ref.header().rtype, DateTimezone::Utc)?,
+        metadata,
+    );
+    Ok((instrument_id, date))
+}
+
+fn resolve_for_date(
+    instrument_id: u32,
+    date: time::Date,
+    publisher_id: PublisherId,
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+    venue_resolver: impl Fn(&Symbol, PublisherId) -> Option<Venue>,
+) -> Result<ResolvedInstrument, SymbologyError> {
+    let symbol_map = build_symbol_map(metadata, date)?;
+    let raw_symbol = raw_symbol_for_date(&symbol_map, instrument_id, publisher_id, date)?;
+
+    let instrument_id = resolve_instrument_id(
+        Ustr::from(raw_symbol),
+        Some(instrument_id),
+        publisher_id,
+        publisher_venue_map,
+        glbx_exchange_map,
+        &ResolveOptions {
+            venue_resolver: Some(&venue_resolver),
+            ..ResolveOptions::default()
+        },
+    )?;
+
+    let leg_count = spread_leg_count(instrument_id.symbol.value.as_str());
+    Ok(ResolvedInstrument {
+        instrument_id,
+        raw_symbol: instrument_id.symbol.value,
+        publisher_id,
+        dataset: dataset_for_publisher(publisher_id, metadata).map(Ustr::from),
+        continuous_symbol: None,
+        is_spread: leg_count > 1,
+        leg_count,
+        asset_class: None,
+        instrument_class: None,
+    })
+}
+
+/// Returns the Nautilus `InstrumentId`s for the given records, in input order.
+///
+/// The symbol map for each date is only built once, so this is more efficient than calling
+/// `decode_nautilus_instrument_id` for each record. The `publisher_id` of each record is taken
+/// from its header, and an error for the first record which cannot be resolved includes its
+/// index.
+pub fn decode_nautilus_instrument_ids(
+    records: &[dbn::RecordRef],
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<Vec<InstrumentId>, SymbologyError> {
+    let mut symbol_maps: HashMap<time::Date, PitSymbolMap> = HashMap::new();
+    let mut instrument_ids = Vec::with_capacity(records.len());
+
+    for (i, rec_ref) in records.iter().enumerate() {
+        let publisher_id = rec_ref.header().publisher_id;
+        let mut resolve = || -> Result<InstrumentId, SymbologyError> {
+            let (instrument_id, nanoseconds) = decode_instrument_id_and_ts(
+                rec_ref,
+                TimestampSource::default(),
+                0,
+                0,
+                metadata.start,
+            )?;
+            let date = clamp_to_metadata_dates(
+                ts_to_date(nanoseconds, rec_ref.header().rtype, DateTimezone::Utc)?,
+                metadata,
+            );
+            let symbol_map = match symbol_maps.entry(date) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(build_symbol_map(metadata, date)?),
+            };
+            let raw_symbol = raw_symbol_for_date(symbol_map, instrument_id, publisher_id, date)?;
+
+            resolve_instrument_id(
+                Ustr::from(raw_symbol),
+                Some(instrument_id),
+                publisher_id,
+                publisher_venue_map,
+                glbx_exchange_map,
+                &ResolveOptions::default(),
+            )
+        };
+        let instrument_id = resolve().map_err(|e| SymbologyError::Record {
+            index: i,
+            source: Box::new(e),
+        })?;
+        instrument_ids.push(instrument_id);
+    }
+
+    Ok(instrument_ids)
+}
+
+/// Returns the Nautilus `InstrumentId` for the given Databento `raw_symbol` and `publisher_id`.
+///
+/// This applies the same venue resolution as `decode_nautilus_instrument_id` without requiring
+/// the DBN metadata, for when the raw symbol is already known (e.g. replaying stored records).
+pub fn instrument_id_from_raw(
+    raw_symbol: &str,
+    publisher_id: PublisherId,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<InstrumentId, SymbologyError> {
+    resolve_instrument_id(
+        Ustr::from(raw_symbol),
+        None,
+        publisher_id,
+        publisher_venue_map,
+        glbx_exchange_map,
+        &ResolveOptions::default(),
+    )
+}
+
+/// Returns the Nautilus `InstrumentId` for the given Databento `raw_symbol` and `venue_mic`,
+/// without any publisher or venue map lookup.
+///
+/// # Errors
+///
+/// Returns an error if `raw_symbol` is empty or whitespace-only, or if `venue_mic` is not a
+/// known ISO 10383 market identifier code (MIC).
+pub fn instrument_id(raw_symbol: &str, venue_mic: &str) -> anyhow::Result<InstrumentId> {
+    let symbol = Symbol::new(raw_symbol)?;
+    let venue = Venue::new(venue_mic)?;
+    anyhow::ensure!(
+        venue.is_mic(),
+        "Invalid venue '{venue_mic}' for {symbol}, not a known ISO 10383 MIC"
+    );
+    Ok(InstrumentId::new(symbol, venue))
+}
+
+/// Returns the Databento dataset (e.g. `GLBX.MDP3`) for the given `publisher_id`.
+///
+/// Publishers unknown to DBN fall back to the dataset of the `metadata`, and `None` is returned
+/// if the metadata has no dataset.
+#[must_use]
+pub fn dataset_for_publisher(publisher_id: PublisherId, metadata: &dbn::Metadata) -> Option<&str> {
+    match dbn::Publisher::try_from(publisher_id) {
+        Ok(publisher) => Some(publisher.dataset().as_str()),
+        Err(_) if metadata.dataset.is_empty() => None,
+        Err(_) => Some(metadata.dataset.as_str()),
+    }
+}
+
+/// Returns every Nautilus `InstrumentId` the `metadata` can resolve to on `date`, ordered by
+/// Databento `instrument_id`, along with the errors for any symbols which were skipped.
+///
+/// Venues are resolved with the first publisher in the `publisher_venue_map` belonging to the
+/// metadata dataset. Symbols which can't be resolved (such as invalid raw symbols) are skipped
+/// and logged rather than failing the whole call, so that the skipped symbols can be reported.
+pub fn enumerate_instruments(
+    metadata: &dbn::Metadata,
+    date: time::Date,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<(Vec<InstrumentId>, Vec<SymbologyError>), SymbologyError> {
+    let publisher_id = dataset_publisher(metadata, publisher_venue_map)?;
+    let symbol_map = build_symbol_map(metadata, date)?;
+    let mut symbols: Vec<(&u32, &String)> = symbol_map.inner().iter().collect();
+    symbols.sort_unstable_by_key(|(instrument_id, _)| **instrument_id);
+
+    let mut instrument_ids = Vec::with_capacity(symbols.len());
+    let mut skipped = Vec::new();
+    for (instrument_id, raw_symbol) in symbols {
+        match resolve_instrument_id(
+            Ustr::from(raw_symbol),
+            Some(*instrument_id),
+            publisher_id,
+            publisher_venue_map,
+            glbx_exchange_map,
+            &ResolveOptions::default(),
+        ) {
+            Ok(instrument_id) => instrument_ids.push(instrument_id),
+            Err(e) => {
+                tracing::warn!("Skipping symbol {raw_symbol}: {e}");
+                skipped.push(e);
+            }
+        }
+    }
+
+    Ok((instrument_ids, skipped))
+}
+
+/// Represents a symbology gap for a Databento `instrument_id` on a date of a coverage report.
+#[derive(Debug)]
+pub enum CoverageGap {
+    /// The `instrument_id` is mapped on other dates of the metadata, but not on this date.
+    MissingSymbol { instrument_id: u32 },
+    /// The raw symbol mapped for the `instrument_id` can't be resolved to a Nautilus
+    /// `InstrumentId` (such as an invalid raw symbol, or a venue which can't be resolved).
+    Unresolved {
+        instrument_id: u32,
+        raw_symbol: Ustr,
+        error: SymbologyError,
+    },
+}
+
+impl CoverageGap {
+    /// Return the Databento `instrument_id` of the gap.
+    #[must_use]
+    pub fn instrument_id(&self) -> u32 {
+        match self {
+            Self::MissingSymbol { instrument_id } | Self::Unresolved { instrument_id, .. } => {
+                *instrument_id
+            }
+        }
+    }
+}
+
+/// Represents the symbology coverage of the date range of a DBN file's metadata.
+#[derive(Debug, Default)]
+pub struct CoverageReport {
+    /// The dates checked, from the metadata `start` to the last date before `end`.
+    pub dates: Vec<time::Date>,
+    /// The gaps for each date with any, ordered by Databento `instrument_id`.
+    pub gaps: BTreeMap<time::Date, Vec<CoverageGap>>,
+}
+
+impl CoverageReport {
+    /// Return whether every instrument of the metadata resolves on every date.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.gaps.is_empty()
+    }
+}
+
+/// Returns a report of the symbology gaps of the `metadata` over its date range, without
+/// resolving any records, as a pre-flight check before processing a file.
+///
+/// Every Databento `instrument_id` mapped on any date of the range is checked on each date,
+/// and is reported if it has no symbol on that date or its symbol can't be resolved. Venues are
+/// resolved as for `enumerate_instruments`. If the metadata has no `end` then only the date of
+/// `start` is checked.
+pub fn coverage_report(
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &HashMap<Symbol, Venue>,
+) -> Result<CoverageReport, SymbologyError> {
+    let publisher_id = dataset_publisher(metadata, publisher_venue_map)?;
+
+    // The metadata `end` is exclusive, so the last date is that of the nanosecond before
+    let start_date = metadata.start().date();
+    let end_date = metadata.end().map_or(start_date, |end| {
+        (end - time::Duration::nanoseconds(1)).date()
+    });
+
+    let mut symbol_maps = Vec::new();
+    let mut date = start_date;
+    while date <= end_date {
+        symbol_maps.push((date, build_symbol_map(metadata, date)?));
+        match date.next_day() {
+            Some(next_date) => date = next_date,
+            None => break,
+        }
+    }
+
+    let instrument_ids: BTreeSet<u32> = symbol_maps
+        .iter()
+        .flat_map(|(_, symbol_map)| symbol_map.inner().keys().copied())
+        .collect();
+
+    let mut report = CoverageReport::default();
+    for (date, symbol_map) in &symbol_maps {
+        let mut gaps = Vec::new();
+        for instrument_id in &instrument_ids {
+            let Some(raw_symbol) = symbol_map.get(*instrument_id) else {
+                gaps.push(CoverageGap::MissingSymbol {
+                    instrument_id: *instrument_id,
+                });
+                continue;
+            };
+            let raw_symbol = Ustr::from(raw_symbol);
+            if let Err(error) = resolve_instrument_id(
+                raw_symbol,
+                Some(*instrument_id),
+                publisher_id,
+                publisher_venue_map,
+                glbx_exchange_map,
+                &ResolveOptions::default(),
+            ) {
+                gaps.push(CoverageGap::Unresolved {
+                    instrument_id: *instrument_id,
+                    raw_symbol,
+                    error,
+                });
+            }
+        }
+
+        report.dates.push(*date);
+        if !gaps.is_empty() {
+            report.gaps.insert(*date, gaps);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Returns the first publisher in the `publisher_venue_map` belonging to the metadata dataset.
+fn dataset_publisher(
+    metadata: &dbn::Metadata,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+) -> Result<PublisherId, SymbologyError> {
+    publisher_venue_map
+        .keys()
+        .copied()
+        .find(|publisher_id| is_dataset_publisher(*publisher_id, &[metadata.dataset.as_str()]))
+        .ok_or_else(|| SymbologyError::UnknownDataset {
+            dataset: metadata.dataset.clone(),
+        })
+}
+
+/// Returns the Nautilus `InstrumentId` of the underlying of the given instrument definition
+/// (such as the future of a futures option), or `None` if the definition has no underlying.
+///
+/// The underlying raw symbol is resolved with the venue logic of the `resolver`, using the
+/// `publisher_id` of the definition. The underlyings of OPRA equity options are listed on other
+/// venues than the option, so cannot be resolved from an OPRA publisher (as they are not OSI
+/// symbols).
+pub fn underlying_instrument_id<P: SymbolMapProvider>(
+    def: &dbn::InstrumentDefMsg,
+    resolver: &DatabentoSymbologyResolver<P>,
+) -> Result<Option<InstrumentId>, SymbologyError> {
+    let underlying = def.underlying()?.trim();
+    if underlying.is_empty() {
+        return Ok(None);
+    }
+
+    let underlying_id = (def.underlying_id != 0).then_some(def.underlying_id);
+    resolver
+        .resolve_raw_symbol(Ustr::from(underlying), underlying_id, def.hd.publisher_id)
+        .map(Some)
+}
+
+/// Returns the Databento `instrument_id` for the given Nautilus `instrument_id` on `date`.
+///
+/// This is the inverse of `decode_nautilus_instrument_id`. The venue must be known to the
+/// `publisher_venue_map` (or be a CME Globex exchange). The candidates are the Databento
+/// `instrument_id`s whose raw symbol (with any of the `symbol_aliases` applied) is the symbol
+/// on `date`. As the same raw symbol can be mapped for several publishers, candidates with a
+/// publisher in `instrument_publishers` (such as collected from record headers) are only kept if
+/// that publisher maps to the venue.
+///
+/// An error is returned if no candidate remains, or if more than one does.
+pub fn encode_databento_instrument_id(
+    instrument_id: &InstrumentId,
+    metadata: &dbn::Metadata,
+    date: time::Date,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    symbol_aliases: Option<&HashMap<Ustr, Ustr>>,
+    instrument_publishers: &HashMap<u32, PublisherId>,
+) -> Result<u32, SymbologyError> {
+    let venue = instrument_id.venue;
+    let is_known_venue = publisher_venue_map.values().any(|v| *v == venue)
+        || (metadata.dataset == GLBX_DATASET && Venue::from_code(venue.value.as_str()).is_ok());
+    if !is_known_venue {
+        return Err(SymbologyError::UnknownVenue {
+            instrument_id: *instrument_id,
+        });
+    }
+
+    let is_venue_publisher = |publisher_id: PublisherId| {
+        publisher_venue_map.get(&publisher_id) == Some(&venue)
+            || (is_glbx_publisher(publisher_id) && Venue::from_code(venue.value.as_str()).is_ok())
+    };
+
+    let symbol_map = build_symbol_map(metadata, date)?;
+    let mut candidates: Vec<u32> = symbol_map
+        .inner()
+        .iter()
+        .filter(|(_, raw_symbol)| {
+            let raw_symbol = Ustr::from(raw_symbol.as_str());
+            let symbol = symbol_aliases
+                .and_then(|aliases| aliases.get(&raw_symbol).copied())
+                .unwrap_or(raw_symbol);
+            symbol == instrument_id.symbol.value
+        })
+        .filter(|(id, _)| {
+            instrument_publishers
+                .get(*id)
+                .map_or(true, |publisher_id| is_venue_publisher(*publisher_id))
+        })
+        .map(|(id, _)| *id)
+        .collect();
+
+    match candidates.len() {
+        0 => Err(SymbologyError::UnmappedInstrument {
+            instrument_id: *instrument_id,
+            date,
+        }),
+        1 => Ok(candidates[0]),
+        _ => {
+            candidates.sort_unstable();
+            Err(SymbologyError::AmbiguousInstrument {
+                instrument_id: *instrument_id,
+                date,
+                candidates,
+            })
+        }
+    }
+}
+
+/// Returns the string representation of the given `instrument_id` in the given format.
+///
+/// The `InstrumentId` itself is always canonical, this only renders alternate forms for
+/// consumers which expect them.
+#[must_use]
+pub fn format_instrument_id(instrument_id: &InstrumentId, fmt: IdFormat) -> String {
+    match fmt {
+        IdFormat::SymbolDotVenue => instrument_id.to_string(),
+        IdFormat::VenueColonSymbol => {
+            format!("{}:{}", instrument_id.venue, instrument_id.symbol)
+        }
+    }
+}
+
+/// Returns the Nautilus asset class and instrument class for the given Databento
+/// `instrument_class` code.
+///
+/// Databento only classifies the instrument type, so the asset class of derivatives defaults to
+/// commodity (as for the instrument decoders), and should be refined from the CFI code where
+/// possible (see `resolve_full`).
+pub fn parse_instrument_class(
+    instrument_class: c_char,
+) -> Result<(AssetClass, InstrumentClass), SymbologyError> {
+    let classes = match instrument_class as u8 as char {
+        'B' => (AssetClass::Debt, InstrumentClass::Bond),
+        'C' | 'P' => (AssetClass::Commodity, InstrumentClass::Option),
+        'F' => (AssetClass::Commodity, InstrumentClass::Future),
+        'K' => (AssetClass::Equity, InstrumentClass::Spot),
+        'M' | 'T' => (AssetClass::Commodity, InstrumentClass::OptionSpread),
+        'S' => (AssetClass::Commodity, InstrumentClass::FutureSpread),
+        'X' => (AssetClass::FX, InstrumentClass::Spot),
+        c => {
+            return Err(SymbologyError::UnknownInstrumentClass {
+                instrument_class: c,
+            })
+        }
+    };
+    Ok(classes)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use dbn::{MappingInterval, SType, SymbolMapping};
+    use rstest::rstest;
+
+    use super::*;
+    use crate::databento::stubs::{
+        make_date, make_definition_record, make_esh4_metadata, make_imbalance_record,
+        make_ohlcv_record, make_publisher_venue_map, make_share_class_metadata, make_stat_record,
+        make_status_record, make_symbol_mapping, make_trade_record, TS_2024_01_02,
+    };
+
+    #[rstest]
+    fn test_decode_instrument_id() {
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_with_unmapped_instrument_id_returns_err() {
+        let msg = make_trade_record(9999, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let result = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err,
+            SymbologyError::MissingSymbol {
+                instrument_id: 9999,
+                publisher_id: 1,
+                ..
+            }
+        ));
+        let err = err.to_string();
+        assert!(err.contains("`instrument_id` 9999"));
+        assert!(err.contains("`publisher_id` 1"));
+        assert!(err.contains("2024-01-02"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_with_unmapped_publisher_returns_err() {
+        let msg = make_trade_record(5602, 99, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let result = decode_nautilus_instrument_id(
+            &rec_ref,
+            99,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("`publisher_id` 99"));
+        assert!(matches!(
+            err,
+            SymbologyError::UnknownPublisher {
+                publisher_id: 99,
+                instrument_id: Some(5602),
+            }
+        ));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("  ")]
+    #[case("\0\0\0")]
+    fn test_decode_instrument_id_with_empty_raw_symbol_returns_err(#[case] raw_symbol: &str) {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].raw_symbol = raw_symbol.to_string();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let result = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err,
+            SymbologyError::InvalidSymbol { publisher_id: 1 }
+        ));
+        assert!(err.to_string().contains("Invalid empty raw symbol"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_trims_null_padded_raw_symbol() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].raw_symbol = "ESH4\0\0\0".to_string();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(instrument_id.symbol.value.as_str(), "ESH4");
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_zero_id_single_symbol() {
+        let msg = make_ohlcv_record(dbn::rtype::OHLCV_1M, 0, 1, TS_2024_01_02);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_zero_id_multi_symbol() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings.push(make_symbol_mapping("NQH4", 5603));
+        let msg = make_ohlcv_record(dbn::rtype::OHLCV_1M, 0, 1, TS_2024_01_02);
+
+        let result = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::ZeroInstrumentId {
+                publisher_id: 1,
+                symbol_count: 2,
+                ..
+            }
+        ));
+    }
+
+    #[rstest]
+    fn test_enumerate_instruments() {
+        let mut metadata = make_esh4_metadata();
+        for (raw_symbol, instrument_id) in [("NQH4", 5603), ("", 5604)] {
+            metadata
+                .mappings
+                .push(make_symbol_mapping(raw_symbol, instrument_id));
+        }
+        let glbx_exchange_map = HashMap::from([(Symbol::from("NQH4"), Venue::XCME())]);
+
+        let (instrument_ids, skipped) = enumerate_instruments(
+            &metadata,
+            make_date(2),
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+
+        assert_eq!(
+            instrument_ids,
+            vec![
+                InstrumentId::from("ESH4.GLBX"),
+                InstrumentId::from("NQH4.XCME")
+            ]
+        );
+        assert_eq!(skipped.len(), 1);
+        assert!(matches!(
+            skipped[0],
+            SymbologyError::InvalidSymbol { publisher_id: 1 }
+        ));
+    }
+
+    #[rstest]
+    fn test_coverage_report() {
+        let mut metadata = make_esh4_metadata();
+        for (raw_symbol, instrument_id, end_day) in [("NQH4", "5603", 3), ("", "5604", 4)] {
+            metadata.mappings.push(SymbolMapping {
+                raw_symbol: raw_symbol.to_string(),
+                intervals: vec![MappingInterval {
+                    start_date: make_date(1),
+                    end_date: make_date(end_day),
+                    symbol: instrument_id.to_string(),
+                }],
+            });
+        }
+
+        let report =
+            coverage_report(&metadata, &make_publisher_venue_map(), &HashMap::new()).unwrap();
+
+        assert!(!report.is_complete());
+        assert_eq!(report.dates, vec![make_date(1), make_date(2), make_date(3)]);
+        let gap_ids = |day: u8| -> Vec<u32> {
+            report.gaps[&make_date(day)]
+                .iter()
+                .map(CoverageGap::instrument_id)
+                .collect()
+        };
+        assert_eq!(gap_ids(1), vec![5604]);
+        assert_eq!(gap_ids(2), vec![5604]);
+        assert_eq!(gap_ids(3), vec![5603, 5604]);
+        let day_3_gaps = &report.gaps[&make_date(3)];
+        assert!(matches!(
+            day_3_gaps[0],
+            CoverageGap::MissingSymbol {
+                instrument_id: 5603
+            }
+        ));
+        assert!(matches!(
+            day_3_gaps[1],
+            CoverageGap::Unresolved {
+                error: SymbologyError::InvalidSymbol { .. },
+                ..
+            }
+        ));
+    }
+
+    #[rstest]
+    fn test_coverage_report_complete() {
+        let report = coverage_report(
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert!(report.is_complete());
+        assert_eq!(report.dates.len(), 3);
+    }
+
+    #[rstest]
+    fn test_enumerate_instruments_with_unknown_dataset_returns_err() {
+        let publisher_venue_map = IndexMap::from([(2, Venue::from("XNAS"))]);
+
+        let result = enumerate_instruments(
+            &make_esh4_metadata(),
+            make_date(2),
+            &publisher_venue_map,
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::UnknownDataset { dataset } if dataset == "GLBX.MDP3"
+        ));
+    }
+
+    #[rstest]
+    #[case(1, "GLBX.MDP3", Some("GLBX.MDP3"))]
+    #[case(2, "GLBX.MDP3", Some("XNAS.ITCH"))]
+    #[case(u16::MAX, "GLBX.MDP3", Some("GLBX.MDP3"))]
+    #[case(u16::MAX, "", None)]
+    fn test_dataset_for_publisher(
+        #[case] publisher_id: PublisherId,
+        #[case] dataset: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let mut metadata = make_esh4_metadata();
+        metadata.dataset = dataset.to_string();
+
+        assert_eq!(dataset_for_publisher(publisher_id, &metadata), expected);
+    }
+
+    #[rstest]
+    #[case::first_record(1_704_067_200_000_000_000 - 1)] // 2023-12-31T23:59:59.999999999Z
+    #[case::last_record(1_704_326_400_000_000_000)] // 2024-01-04T00:00:00Z
+    fn test_decode_instrument_id_clamps_boundary_date(#[case] ts_recv: u64) {
+        let msg = make_trade_record(5602, 1, ts_recv);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    #[case(HashMap::new(), "GLBX")]
+    #[case(HashMap::from([(Symbol::from("ES"), Venue::XCME())]), "XCME")]
+    fn test_resolve_venue_matches_full_resolution(
+        #[case] glbx_exchange_map: HashMap<Symbol, Venue>,
+        #[case] expected: &str,
+    ) {
+        let metadata = make_esh4_metadata();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let venue = resolve_venue(
+            &rec_ref,
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+        let instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+
+        assert_eq!(venue, Venue::from(expected));
+        assert_eq!(venue, instrument_id.venue);
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_outside_boundary_date() {
+        let one_day_ns = 86_400_000_000_000;
+        let msg = make_trade_record(5602, 1, 1_704_326_400_000_000_000 + one_day_ns);
+
+        let result = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        assert!(matches!(result.unwrap_err(), SymbologyError::Dbn(_)));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_dbn_v1_metadata() {
+        let mut v1_metadata = make_esh4_metadata();
+        v1_metadata.version = 1;
+        let mut v2_metadata = make_esh4_metadata();
+        v2_metadata.version = 2;
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let v1_instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &v1_metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let v2_instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &v2_metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(v1_instrument_id, v2_instrument_id);
+        assert_eq!(v1_instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_with_venue_resolver() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings.push(make_symbol_mapping("NQH4", 5603));
+        let glbx_exchange_map = HashMap::from([
+            (Symbol::from("ESH4"), Venue::XCME()),
+            (Symbol::from("NQH4"), Venue::XCME()),
+        ]);
+        let custom_venue = Venue::from("CUSTOM");
+        let venue_resolver = |symbol: &Symbol, _: PublisherId| {
+            (symbol.value.as_str() == "ESH4").then_some(custom_venue)
+        };
+        let msg1 = make_trade_record(5602, 1, TS_2024_01_02);
+        let msg2 = make_trade_record(5603, 1, TS_2024_01_02);
+
+        let instrument_id1 = decode_nautilus_instrument_id_with(
+            &dbn::RecordRef::from(&msg1),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+            venue_resolver,
+        )
+        .unwrap();
+        let instrument_id2 = decode_nautilus_instrument_id_with(
+            &dbn::RecordRef::from(&msg2),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+            venue_resolver,
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id1, InstrumentId::from("ESH4.CUSTOM"));
+        assert_eq!(instrument_id2, InstrumentId::from("NQH4.XCME"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_ids_preserves_order() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings.push(make_symbol_mapping("NQH4", 5603));
+        let one_day_ns = 86_400_000_000_000;
+        let msgs = [
+            make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns),
+            make_trade_record(5603, 1, TS_2024_01_02),
+            make_trade_record(5602, 1, TS_2024_01_02),
+            make_trade_record(5603, 1, TS_2024_01_02 + one_day_ns),
+        ];
+        let records: Vec<dbn::RecordRef> = msgs.iter().map(dbn::RecordRef::from).collect();
+
+        let instrument_ids = decode_nautilus_instrument_ids(
+            &records,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            instrument_ids,
+            vec![
+                InstrumentId::from("ESH4.GLBX"),
+                InstrumentId::from("NQH4.GLBX"),
+                InstrumentId::from("ESH4.GLBX"),
+                InstrumentId::from("NQH4.GLBX"),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_decode_instrument_ids_err_includes_index() {
+        let msgs = [
+            make_trade_record(5602, 1, TS_2024_01_02),
+            make_trade_record(5602, 1, TS_2024_01_02),
+            make_trade_record(9999, 1, TS_2024_01_02),
+        ];
+        let records: Vec<dbn::RecordRef> = msgs.iter().map(dbn::RecordRef::from).collect();
+
+        let result = decode_nautilus_instrument_ids(
+            &records,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        let err = result.unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Error resolving record at index 2: "));
+        match err {
+            SymbologyError::Record { index, source } => {
+                assert_eq!(index, 2);
+                assert!(matches!(
+                    *source,
+                    SymbologyError::MissingSymbol {
+                        instrument_id: 9999,
+                        ..
+                    }
+                ));
+            }
+            e => panic!("Unexpected error {e}"),
+        }
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_full() {
+        let metadata = make_esh4_metadata();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let resolved = decode_nautilus_instrument_id_full(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(resolved.instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(
+            resolved.raw_symbol.as_str(),
+            metadata.mappings[0].raw_symbol
+        );
+        assert_eq!(resolved.publisher_id, 1);
+        assert_eq!(resolved.continuous_symbol, None);
+    }
+
+    #[rstest]
+    #[case(&[("ESH4", "XCBT"), ("ES", "XCME")], "ESH4.XCBT")] // Exact hit
+    #[case(&[("ES", "XCME")], "ESH4.XCME")] // Product root fallback hit
+    #[case(&[("NQ", "XCME")], "ESH4.GLBX")] // Miss falls back to the publisher venue
+    fn test_decode_instrument_id_glbx_product_root_fallback(
+        #[case] entries: &[(&str, &str)],
+        #[case] expected: &str,
+    ) {
+        let glbx_exchange_map: HashMap<Symbol, Venue> = entries
+            .iter()
+            .map(|(symbol, venue)| (Symbol::from(*symbol), Venue::from(*venue)))
+            .collect();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    #[case("ESH4", 1, "ESH4.XCME")] // GLBX exchange map takes precedence
+    #[case("ESM4", 1, "ESM4.XCME")] // GLBX product root fallback
+    #[case("NQH4", 1, "NQH4.GLBX")] // Publisher venue fallback
+    fn test_instrument_id_from_raw(
+        #[case] raw_symbol: &str,
+        #[case] publisher_id: PublisherId,
+        #[case] expected: &str,
+    ) {
+        let glbx_exchange_map = HashMap::from([
+            (Symbol::from("ESH4"), Venue::XCME()),
+            (Symbol::from("ES"), Venue::XCME()),
+        ]);
+
+        let instrument_id = instrument_id_from_raw(
+            raw_symbol,
+            publisher_id,
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    fn test_instrument_id_from_raw_with_unmapped_publisher_returns_err() {
+        let result =
+            instrument_id_from_raw("AAPL", 99, &make_publisher_venue_map(), &HashMap::new());
+
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "No venue found for `publisher_id` 99");
+        assert!(matches!(
+            err,
+            SymbologyError::UnknownPublisher {
+                publisher_id: 99,
+                instrument_id: None,
+            }
+        ));
+    }
+
+    #[rstest]
+    #[case("ESH4", "XCME", "ESH4.XCME")]
+    #[case("AAPL  240119C00150000", "OPRA", "AAPL  240119C00150000.OPRA")]
+    fn test_instrument_id(
+        #[case] raw_symbol: &str,
+        #[case] venue_mic: &str,
+        #[case] expected: &str,
+    ) {
+        let instrument_id = instrument_id(raw_symbol, venue_mic).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    #[case("", "XCME")]
+    #[case("  ", "XCME")]
+    #[case("ESH4", "")]
+    #[case("ESH4", "ZZZZ")]
+    #[case("ESH4", "xcme")]
+    fn test_instrument_id_with_invalid_input_returns_err(
+        #[case] raw_symbol: &str,
+        #[case] venue_mic: &str,
+    ) {
+        assert!(instrument_id(raw_symbol, venue_mic).is_err());
+    }
+
+    #[rstest]
+    #[case("AAPL  240119C00150000")]
+    #[case("SPXW  240119P04700000")]
+    fn test_instrument_id_from_raw_opra_osi_symbol(#[case] raw_symbol: &str) {
+        let publisher_venue_map = IndexMap::from([(22, Venue::from("XCBO"))]);
+
+        let instrument_id =
+            instrument_id_from_raw(raw_symbol, 22, &publisher_venue_map, &HashMap::new()).unwrap();
+
+        assert_eq!(instrument_id.symbol.value.as_str(), raw_symbol);
+        assert_eq!(instrument_id.venue, Venue::from("XCBO"));
+    }
+
+    #[rstest]
+    #[case("AAPL 240119C00150000")]
+    #[case("AAPL  240119X00150000")]
+    #[case("AAPL  2401A9C00150000")]
+    #[case("      240119C00150000")]
+    fn test_instrument_id_from_raw_opra_invalid_osi_symbol(#[case] raw_symbol: &str) {
+        let publisher_venue_map = IndexMap::from([(22, Venue::from("XCBO"))]);
+
+        let result = instrument_id_from_raw(raw_symbol, 22, &publisher_venue_map, &HashMap::new());
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::InvalidOsiSymbol {
+                publisher_id: 22,
+                ..
+            }
+        ));
+    }
+
+    #[rstest]
+    fn test_instrument_id_from_raw_with_empty_raw_symbol_returns_err() {
+        let result = instrument_id_from_raw("", 1, &make_publisher_venue_map(), &HashMap::new());
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_for_date_agrees_with_timestamp_path() {
+        let metadata = make_esh4_metadata();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let instrument_id_for_date = decode_nautilus_instrument_id_for_date(
+            &rec_ref,
+            1,
+            make_date(2),
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id_for_date, instrument_id);
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_for_date_outside_mapping_returns_err() {
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let result = decode_nautilus_instrument_id_for_date(
+            &dbn::RecordRef::from(&msg),
+            1,
+            make_date(4), // Mapping interval end is exclusive
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::MissingSymbol { .. } | SymbologyError::Dbn(_)
+        ));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_stat_msg() {
+        let msg = make_stat_record(5602, 1, TS_2024_01_02, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_stat_msg_with_undef_ts_recv() {
+        let msg = make_stat_record(5602, 1, TS_2024_01_02, dbn::UNDEF_TIMESTAMP);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_imbalance_msg() {
+        let msg = make_imbalance_record(5602, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+        assert_eq!(rec_ref.rtype().unwrap(), dbn::RType::Imbalance);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_imbalance_msg_glbx_exchange() {
+        let msg = make_imbalance_record(5602, 1, TS_2024_01_02);
+        let glbx_exchange_map = HashMap::from([(Symbol::from("ESH4"), Venue::XCME())]);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.XCME"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_status_msg() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].intervals[0].start_date = make_date(2);
+        // Event on 2024-01-01 received on 2024-01-02, only mapped from 2024-01-02
+        let msg = make_status_record(5602, 1, TS_2024_01_02 - 1, TS_2024_01_02);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_with_undef_timestamps_uses_metadata_start() {
+        let mut metadata = make_esh4_metadata();
+        // Only mapped on the metadata start date of 2024-01-01
+        metadata.mappings[0].intervals[0].end_date = make_date(2);
+        let mut msg = make_trade_record(5602, 1, dbn::UNDEF_TIMESTAMP);
+        msg.hd.ts_event = dbn::UNDEF_TIMESTAMP;
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_with_out_of_range_timestamp_returns_err() {
+        let ts = u64::try_from(i64::MAX).unwrap() + 1;
+        let msg = make_trade_record(5602, 1, ts);
+
+        let result = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid timestamp {ts} for record with `rtype` 0x00")
+        );
+        assert!(matches!(
+            err,
+            SymbologyError::InvalidTimestamp { rtype: 0x00, .. }
+        ));
+    }
+
+    #[rstest]
+    fn test_resolve_with_symbol_matches_full_path() {
+        let glbx_exchange_map = HashMap::from([(Symbol::from("ES"), Venue::XCME())]);
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let instrument_id = resolve_with_symbol(
+            &rec_ref,
+            1,
+            "ESH4",
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+        let expected = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, expected);
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.XCME"));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("   ")]
+    fn test_resolve_with_symbol_with_empty_symbol_returns_err(#[case] raw_symbol: &str) {
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let result = resolve_with_symbol(
+            &dbn::RecordRef::from(&msg),
+            1,
+            raw_symbol,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::InvalidSymbol { publisher_id: 1 }
+        ));
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_with_unsupported_rtype_returns_err() {
+        let msg = dbn::ErrorMsg::new(TS_2024_01_02, "Test error");
+
+        let result = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::UnsupportedRType {
+                rtype: dbn::rtype::ERROR
+            }
+        ));
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_round_trip() {
+        let metadata = make_esh4_metadata();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        let result = encode_databento_instrument_id(
+            &instrument_id,
+            &metadata,
+            make_date(2),
+            &make_publisher_venue_map(),
+            None,
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result, msg.hd.instrument_id);
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_round_trip_glbx_exchange() {
+        let metadata = make_esh4_metadata();
+        let glbx_exchange_map = HashMap::from([(Symbol::from("ESH4"), Venue::XCME())]);
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let instrument_id = decode_nautilus_instrument_id(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &metadata,
+            &make_publisher_venue_map(),
+            &glbx_exchange_map,
+        )
+        .unwrap();
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.XCME"));
+
+        let result = encode_databento_instrument_id(
+            &instrument_id,
+            &metadata,
+            make_date(2),
+            &make_publisher_venue_map(),
+            None,
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result, 5602);
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_with_unknown_symbol_returns_err() {
+        let instrument_id = InstrumentId::from("NQH4.GLBX");
+
+        let result = encode_databento_instrument_id(
+            &instrument_id,
+            &make_esh4_metadata(),
+            make_date(2),
+            &make_publisher_venue_map(),
+            None,
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::UnmappedInstrument { .. }
+        ));
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_with_unknown_venue_returns_err() {
+        let instrument_id = InstrumentId::from("ESH4.XNAS");
+
+        let result = encode_databento_instrument_id(
+            &instrument_id,
+            &make_esh4_metadata(),
+            make_date(2),
+            &make_publisher_venue_map(),
+            None,
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::UnknownVenue { .. }
+        ));
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_with_shared_raw_symbol_filters_by_venue() {
+        let mut metadata = make_share_class_metadata();
+        metadata.mappings.truncate(2);
+        for mapping in &mut metadata.mappings {
+            mapping.raw_symbol = "AAPL".to_string();
+        }
+        let publisher_venue_map =
+            IndexMap::from([(2, Venue::from("XNAS")), (9, Venue::from("XNYS"))]);
+        let instrument_publishers = HashMap::from([(1, 2), (2, 9)]);
+
+        for (instrument_id, publisher_id) in [(1, 2), (2, 9)] {
+            let msg = make_trade_record(instrument_id, publisher_id, TS_2024_01_02);
+            let decoded = decode_nautilus_instrument_id(
+                &dbn::RecordRef::from(&msg),
+                publisher_id,
+                &metadata,
+                &publisher_venue_map,
+                &HashMap::new(),
+            )
+            .unwrap();
+
+            let result = encode_databento_instrument_id(
+                &decoded,
+                &metadata,
+                make_date(2),
+                &publisher_venue_map,
+                None,
+                &instrument_publishers,
+            )
+            .unwrap();
+
+            assert_eq!(result, instrument_id);
+        }
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_with_shared_raw_symbol_and_unknown_publishers_returns_err(
+    ) {
+        let mut metadata = make_share_class_metadata();
+        metadata.mappings.truncate(2);
+        for mapping in &mut metadata.mappings {
+            mapping.raw_symbol = "AAPL".to_string();
+        }
+        let publisher_venue_map = IndexMap::from([(2, Venue::from("XNAS"))]);
+
+        let result = encode_databento_instrument_id(
+            &InstrumentId::from("AAPL.XNAS"),
+            &metadata,
+            make_date(2),
+            &publisher_venue_map,
+            None,
+            &HashMap::new(),
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::AmbiguousInstrument { candidates, .. } if candidates == vec![1, 2]
+        ));
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_with_alias() {
+        let symbol_aliases = HashMap::from([(Ustr::from("ESH4"), Ustr::from("ESH24"))]);
+
+        let result = encode_databento_instrument_id(
+            &InstrumentId::from("ESH24.GLBX"),
+            &make_esh4_metadata(),
+            make_date(2),
+            &make_publisher_venue_map(),
+            Some(&symbol_aliases),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result, 5602);
+    }
+
+    #[rstest]
+    fn test_encode_databento_instrument_id_with_inverse_mappings() {
+        let mut metadata = make_esh4_metadata();
+        metadata.stype_in = Some(SType::InstrumentId);
+        metadata.stype_out = SType::RawSymbol;
+        metadata.mappings = vec![SymbolMapping {
+            raw_symbol: "5602".to_string(),
+            intervals: vec![MappingInterval {
+                start_date: make_date(1),
+                end_date: make_date(4),
+                symbol: "ESH4".to_string(),
+            }],
+        }];
+
+        let result = encode_databento_instrument_id(
+            &InstrumentId::from("ESH4.GLBX"),
+            &metadata,
+            make_date(2),
+            &make_publisher_venue_map(),
+            None,
+            &HashMap::from([(5602, 1)]),
+        )
+        .unwrap();
+
+        assert_eq!(result, 5602);
+    }
+
+    #[rstest]
+    fn test_underlying_instrument_id_for_futures_option() {
+        let resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::from([(Symbol::from("ES"), Venue::XCME())]),
+        );
+        let mut def = make_definition_record(5610, 1, 'C', "OCAFPS");
+        for (i, c) in "ESH4".bytes().enumerate() {
+            def.underlying[i] = c as c_char;
+        }
+        def.underlying_id = 5602;
+
+        let underlying = underlying_instrument_id(&def, &resolver).unwrap();
+
+        assert_eq!(underlying, Some(InstrumentId::from("ESH4.XCME")));
+    }
+
+    #[rstest]
+    fn test_underlying_instrument_id_without_underlying() {
+        let resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let def = make_definition_record(5602, 1, 'F', "FXXXXX");
+
+        assert_eq!(underlying_instrument_id(&def, &resolver).unwrap(), None);
+    }
+
+    #[rstest]
+    #[case('B', AssetClass::Debt, InstrumentClass::Bond)]
+    #[case('C', AssetClass::Commodity, InstrumentClass::Option)]
+    #[case('F', AssetClass::Commodity, InstrumentClass::Future)]
+    #[case('K', AssetClass::Equity, InstrumentClass::Spot)]
+    #[case('S', AssetClass::Commodity, InstrumentClass::FutureSpread)]
+    #[case('T', AssetClass::Commodity, InstrumentClass::OptionSpread)]
+    #[case('X', AssetClass::FX, InstrumentClass::Spot)]
+    fn test_parse_instrument_class(
+        #[case] code: char,
+        #[case] expected_asset_class: AssetClass,
+        #[case] expected_instrument_class: InstrumentClass,
+    ) {
+        let result = parse_instrument_class(code as c_char).unwrap();
+        assert_eq!(result, (expected_asset_class, expected_instrument_class));
+    }
+
+    #[rstest]
+    fn test_parse_instrument_class_unknown() {
+        let result = parse_instrument_class('Z' as c_char);
+        assert!(matches!(
+            result,
+            Err(SymbologyError::UnknownInstrumentClass {
+                instrument_class: 'Z'
+            })
+        ));
+    }
+
+    #[rstest]
+    #[case('Z')]
+    #[case('\0')]
+    fn test_decode_instrument_id_from_definition_with_unknown_class(#[case] code: char) {
+        let msg = make_definition_record(5602, 1, code, "");
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let instrument_id = decode_nautilus_instrument_id(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let resolved = decode_nautilus_instrument_id_full(
+            &rec_ref,
+            1,
+            &make_esh4_metadata(),
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(resolved.instrument_id, instrument_id);
+        assert_eq!(resolved.instrument_class, None);
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_from_map() {
+        let symbol_map = HashMap::from([(5602, Ustr::from("ESH4")), (5603, Ustr::from("NQH4"))]);
+        let msg = make_trade_record(5603, 1, TS_2024_01_02);
+        let unmapped = make_trade_record(9999, 1, TS_2024_01_02);
+
+        let instrument_id = decode_nautilus_instrument_id_from_map(
+            &dbn::RecordRef::from(&msg),
+            1,
+            &symbol_map,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let result = decode_nautilus_instrument_id_from_map(
+            &dbn::RecordRef::from(&unmapped),
+            1,
+            &symbol_map,
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+
+        assert_eq!(instrument_id, InstrumentId::from("NQH4.GLBX"));
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), "missing_symbol");
+        assert!(matches!(
+            err,
+            SymbologyError::MissingMappedSymbol {
+                instrument_id: 9999,
+                publisher_id: 1
+            }
+        ));
+    }
+
+    #[rstest]
+    #[case(IdFormat::SymbolDotVenue, "ESH4.GLBX")]
+    #[case(IdFormat::VenueColonSymbol, "GLBX:ESH4")]
+    fn test_format_instrument_id(#[case] fmt: IdFormat, #[case] expected: &str) {
+        let instrument_id = InstrumentId::from("ESH4.GLBX");
+
+        assert_eq!(format_instrument_id(&instrument_id, fmt), expected);
+    }
+}
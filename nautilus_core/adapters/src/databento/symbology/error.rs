@@ -0,0 +1,182 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+use nautilus_model::identifiers::{instrument_id::InstrumentId, venue::Venue};
+use ustr::Ustr;
+
+use crate::databento::types::PublisherId;
+
+/// Represents an error resolving Databento symbology.
+#[derive(thiserror::Error, Debug)]
+pub enum SymbologyError {
+    /// The `publisher_id` has no venue mapping.
+    ///
+    /// Databento will occasionally add publishers before the venue mapping has been updated,
+    /// so callers can match on this to skip the record rather than aborting the session.
+    #[error(
+        "No venue found for `publisher_id` {publisher_id}{}",
+        .instrument_id.map_or_else(String::new, |id| format!(" (`instrument_id` {id})"))
+    )]
+    UnknownPublisher {
+        publisher_id: PublisherId,
+        /// The Databento instrument ID of the record, if resolving for a record.
+        instrument_id: Option<u32>,
+    },
+    /// The `instrument_id` has no raw symbol in the symbol map for `date`.
+    #[error("No raw symbol found for `instrument_id` {instrument_id}, `publisher_id` {publisher_id} on {date}")]
+    MissingSymbol {
+        instrument_id: u32,
+        publisher_id: PublisherId,
+        date: time::Date,
+    },
+    /// The `instrument_id` has no raw symbol in an externally supplied symbol map.
+    #[error("No raw symbol found for `instrument_id` {instrument_id}, `publisher_id` {publisher_id} in symbol map")]
+    MissingMappedSymbol {
+        instrument_id: u32,
+        publisher_id: PublisherId,
+    },
+    /// The `publisher_id` of the record is not a publisher of the metadata `dataset`.
+    #[error(
+        "`publisher_id` {publisher_id} is not a publisher of the metadata `dataset` {dataset}"
+    )]
+    ForeignPublisher {
+        publisher_id: PublisherId,
+        dataset: String,
+    },
+    /// The record has a zero-filled `instrument_id` and the symbol map for `date` does not
+    /// have exactly one symbol to fall back to.
+    #[error("Cannot resolve zero `instrument_id` for `publisher_id` {publisher_id} on {date}, symbol map has {symbol_count} symbols")]
+    ZeroInstrumentId {
+        publisher_id: PublisherId,
+        date: time::Date,
+        symbol_count: usize,
+    },
+    /// The raw symbol is empty or whitespace-only.
+    #[error("Invalid empty raw symbol for `publisher_id` {publisher_id}")]
+    InvalidSymbol { publisher_id: PublisherId },
+    /// The raw symbol of an OPRA publisher is not a valid OSI symbol.
+    #[error("Invalid OSI raw symbol '{raw_symbol}' for OPRA `publisher_id` {publisher_id}")]
+    InvalidOsiSymbol {
+        raw_symbol: Ustr,
+        publisher_id: PublisherId,
+    },
+    /// The record type is not supported for symbology resolution.
+    #[error("RType {rtype:#04x} is currently unsupported by NautilusTrader")]
+    UnsupportedRType { rtype: u8 },
+    /// The record timestamp cannot be converted to a date.
+    #[error("Invalid timestamp {ts} for record with `rtype` {rtype:#04x}")]
+    InvalidTimestamp { ts: u64, rtype: u8 },
+    /// The venue of the Nautilus instrument ID has no publisher.
+    #[error("No publisher found for `venue` {} of {instrument_id}", .instrument_id.venue)]
+    UnknownVenue { instrument_id: InstrumentId },
+    /// The Nautilus instrument ID has no Databento `instrument_id` on `date`.
+    #[error("No Databento `instrument_id` found for {instrument_id} on {date}")]
+    UnmappedInstrument {
+        instrument_id: InstrumentId,
+        date: time::Date,
+    },
+    /// The Nautilus instrument ID maps to more than one Databento `instrument_id` on `date`.
+    #[error("Ambiguous Databento `instrument_id` for {instrument_id} on {date}, candidates {candidates:?}")]
+    AmbiguousInstrument {
+        instrument_id: InstrumentId,
+        date: time::Date,
+        candidates: Vec<u32>,
+    },
+    /// The record at `index` of a batch could not be resolved.
+    #[error("Error resolving record at index {index}: {source}")]
+    Record {
+        index: usize,
+        source: Box<SymbologyError>,
+    },
+    /// No publisher in the venue map belongs to the `dataset` of the metadata.
+    #[error("No publisher found for `dataset` {dataset}")]
+    UnknownDataset { dataset: String },
+    /// No metadata has been provided for the `dataset` of the record.
+    #[error("No metadata found for `dataset` {dataset} (`publisher_id` {publisher_id})")]
+    MissingMetadata {
+        dataset: String,
+        publisher_id: PublisherId,
+    },
+    /// The symbol maps for `dates` could not be built when prewarming.
+    #[error("Failed to prewarm symbol maps for dates {dates:?}")]
+    PrewarmFailed { dates: Vec<time::Date> },
+    /// No metadata segment contains the record timestamp.
+    #[error("No metadata segment found for timestamp {ts}")]
+    MissingSegment { ts: u64 },
+    /// The resolved venue is not a known ISO 10383 market identifier code (MIC).
+    #[error("Invalid venue {venue} for {instrument_id}, not a known ISO 10383 MIC")]
+    InvalidVenue {
+        venue: Venue,
+        instrument_id: InstrumentId,
+    },
+    /// The record timestamp precedes the metadata `start` by more than the pre-start tolerance.
+    #[error("Timestamp {ts} precedes metadata `start` {start} by more than the tolerance of {tolerance}ns")]
+    BeforeStart { ts: u64, start: u64, tolerance: u64 },
+    /// The `publisher_id` argument differs from the `publisher_id` of the record header.
+    #[error("`publisher_id` {publisher_id} does not match the record header `publisher_id` {header_publisher_id}")]
+    PublisherMismatch {
+        publisher_id: PublisherId,
+        header_publisher_id: PublisherId,
+    },
+    /// The `instrument_class` code of an instrument definition is not a known Databento code.
+    #[error("Unknown `instrument_class` '{instrument_class}'")]
+    UnknownInstrumentClass { instrument_class: char },
+    /// The metadata symbology types have no supported mapping direction to instrument IDs.
+    #[error(
+        "Unsupported metadata symbology `stype_in` {stype_in:?} and `stype_out` {stype_out:?}"
+    )]
+    UnsupportedSTypes {
+        stype_in: Option<dbn::SType>,
+        stype_out: dbn::SType,
+    },
+    /// The DBN stream uses a `compression` which is not supported by `dbn::Compression`.
+    #[error("Unsupported DBN stream compression {compression}, only zstd is supported")]
+    UnsupportedCompression { compression: &'static str },
+    /// The DBN record or metadata is invalid.
+    #[error(transparent)]
+    Dbn(#[from] dbn::Error),
+}
+
+impl SymbologyError {
+    /// Return the name of the error category, for use as a metrics label.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnknownPublisher { .. } => "unknown_publisher",
+            Self::MissingSymbol { .. } | Self::MissingMappedSymbol { .. } => "missing_symbol",
+            Self::ForeignPublisher { .. } => "foreign_publisher",
+            Self::ZeroInstrumentId { .. } => "zero_instrument_id",
+            Self::InvalidSymbol { .. } => "invalid_symbol",
+            Self::InvalidOsiSymbol { .. } => "invalid_osi_symbol",
+            Self::UnsupportedRType { .. } => "unsupported_rtype",
+            Self::InvalidTimestamp { .. } => "invalid_timestamp",
+            Self::UnknownVenue { .. } => "unknown_venue",
+            Self::UnmappedInstrument { .. } => "unmapped_instrument",
+            Self::AmbiguousInstrument { .. } => "ambiguous_instrument",
+            Self::Record { source, .. } => source.kind(),
+            Self::UnknownDataset { .. } => "unknown_dataset",
+            Self::MissingMetadata { .. } => "missing_metadata",
+            Self::PrewarmFailed { .. } => "prewarm_failed",
+            Self::MissingSegment { .. } => "missing_segment",
+            Self::InvalidVenue { .. } => "invalid_venue",
+            Self::BeforeStart { .. } => "before_start",
+            Self::PublisherMismatch { .. } => "publisher_mismatch",
+            Self::UnknownInstrumentClass { .. } => "unknown_instrument_class",
+            Self::UnsupportedSTypes { .. } => "unsupported_stypes",
+            Self::UnsupportedCompression { .. } => "unsupported_compression",
+            Self::Dbn(_) => "dbn",
+        }
+    }
+}
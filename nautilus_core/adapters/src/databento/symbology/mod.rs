@@ -0,0 +1,905 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+mod decode;
+mod error;
+mod multi;
+mod resolver;
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Mutex,
+};
+
+use databento::dbn::{PitSymbolMap, Record};
+use indexmap::IndexMap;
+use nautilus_model::{
+    enums::{AssetClass, InstrumentClass},
+    identifiers::{instrument_id::InstrumentId, symbol::Symbol, venue::Venue},
+};
+use ustr::Ustr;
+
+use crate::databento::{
+    decode::parse_cfi_iso10926,
+    types::PublisherId,
+    venues::{glbx_product_root, GlbxVenueEntry},
+};
+
+pub use self::{
+    decode::{
+        coverage_report, dataset_for_publisher, decode_nautilus_instrument_id,
+        decode_nautilus_instrument_id_for_date, decode_nautilus_instrument_id_from_map,
+        decode_nautilus_instrument_id_full, decode_nautilus_instrument_id_with,
+        decode_nautilus_instrument_ids, encode_databento_instrument_id, enumerate_instruments,
+        format_instrument_id, instrument_id, instrument_id_from_raw, parse_instrument_class,
+        resolve_venue, resolve_with_symbol, underlying_instrument_id, CoverageGap, CoverageReport,
+    },
+    error::SymbologyError,
+    multi::{MultiDatasetResolver, SegmentedSymbologyResolver},
+    resolver::{
+        ControlMsg, DatabentoSymbologyResolver, DatabentoSymbologyResolverBuilder,
+        InvalidVenueEntry, ResolveOutcome, ResolverMetrics, ResolverMetricsSnapshot,
+        SymbolMapProvider, SymbologyRecord,
+    },
+};
+
+const GLBX_DATASET: &str = "GLBX.MDP3";
+const OPRA_DATASET: &str = "OPRA.PILLAR";
+const OSI_SYMBOL_LEN: usize = 21;
+const EQUITY_DATASETS: [&str; 24] = [
+    "ARCX.PILLAR",
+    "BATS.PITCH",
+    "BATY.PITCH",
+    "DBEQ.BASIC",
+    "DBEQ.PLUS",
+    "EDGA.PITCH",
+    "EDGX.PITCH",
+    "EPRL.DOM",
+    "FINN.NLS",
+    "FINY.TRADES",
+    "IEXG.TOPS",
+    "MEMX.MEMOIR",
+    "XASE.PILLAR",
+    "XBOS.ITCH",
+    "XCHI.PILLAR",
+    "XCIS.BBO",
+    "XCIS.PILLAR",
+    "XCIS.TRADES",
+    "XNAS.ITCH",
+    "XNAS.NLS",
+    "XNAS.QBBO",
+    "XNYS.BBO",
+    "XNYS.PILLAR",
+    "XPSX.ITCH",
+];
+const SHARE_CLASS_SEPARATORS: [char; 3] = ['.', '/', ' '];
+
+static WARNED_PUBLISHER_IDS: Mutex<BTreeSet<PublisherId>> = Mutex::new(BTreeSet::new());
+
+/// Represents a string format of a Nautilus `InstrumentId`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IdFormat {
+    /// The canonical Nautilus format `SYMBOL.VENUE` (e.g. `ESH4.GLBX`).
+    #[default]
+    SymbolDotVenue,
+    /// The legacy format `VENUE:SYMBOL` (e.g. `GLBX:ESH4`).
+    VenueColonSymbol,
+}
+
+/// Represents the record timestamp used to select the date for symbol map resolution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TimestampSource {
+    /// The capture-server-received timestamp, falling back to `ts_event` for records without a
+    /// defined `ts_recv` (such as OHLCV bars).
+    #[default]
+    TsRecv,
+    /// The matching-engine-received timestamp from the record header.
+    TsEvent,
+}
+
+/// Represents the timezone in which the date for symbol map resolution is selected.
+///
+/// Databento symbol maps are keyed by the reference timezone of the dataset, which for US
+/// equities is America/New_York, so records just before UTC midnight can otherwise resolve
+/// against the following date.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DateTimezone {
+    /// Coordinated Universal Time.
+    #[default]
+    Utc,
+    /// A fixed offset from UTC, without daylight saving time.
+    Fixed(time::UtcOffset),
+    /// America/New_York, with US daylight saving time applied.
+    NewYork,
+}
+
+impl DateTimezone {
+    /// Return the date of the given `datetime` in the timezone.
+    #[must_use]
+    pub fn date(self, datetime: time::OffsetDateTime) -> time::Date {
+        match self {
+            Self::Utc => datetime.to_offset(time::UtcOffset::UTC).date(),
+            Self::Fixed(offset) => datetime.to_offset(offset).date(),
+            Self::NewYork => datetime.to_offset(new_york_offset(datetime)).date(),
+        }
+    }
+}
+
+/// Represents the canonical share class separator of normalized US equity symbols.
+///
+/// Publishers variously separate the share class of a symbol with `.`, `/` or a space (e.g.
+/// `BRK.B`, `BRK/B` and `BRK B`), and some omit the separator (`BRKB`). As a symbol without a
+/// separator cannot be split, only `None` maps all variants to the same symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShareClassSeparator {
+    /// Separate the share class with a dot (e.g. `BRK.B`).
+    Dot,
+    /// Separate the share class with a slash (e.g. `BRK/B`).
+    Slash,
+    /// Remove the separator (e.g. `BRKB`).
+    None,
+}
+
+impl ShareClassSeparator {
+    /// Return the given US equity `raw_symbol` with all share class separators replaced by
+    /// this canonical separator.
+    #[must_use]
+    pub fn normalize(self, raw_symbol: &str) -> String {
+        let canonical = match self {
+            Self::Dot => Some('.'),
+            Self::Slash => Some('/'),
+            Self::None => None,
+        };
+        raw_symbol
+            .chars()
+            .filter_map(|c| {
+                if SHARE_CLASS_SEPARATORS.contains(&c) {
+                    canonical
+                } else {
+                    Some(c)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Represents the precedence of the venue maps when resolving the venue of a raw symbol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VenueResolutionPolicy {
+    /// The GLBX exchange map is consulted before the publisher venue map.
+    #[default]
+    GlbxFirst,
+    /// The publisher venue map is consulted before the GLBX exchange map.
+    PublisherFirst,
+    /// The GLBX exchange map is only consulted for CME Globex publishers, before the publisher
+    /// venue map.
+    GlbxOnlyForGlbxPublishers,
+}
+
+/// Represents the Databento symbology resolved for a single record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResolvedInstrument {
+    /// The resolved Nautilus instrument ID.
+    pub instrument_id: InstrumentId,
+    /// The Databento raw symbol the instrument ID was resolved from.
+    pub raw_symbol: Ustr,
+    /// The Databento publisher ID of the record.
+    pub publisher_id: PublisherId,
+    /// The Databento dataset of the publisher (e.g. `GLBX.MDP3`), if known.
+    pub dataset: Option<Ustr>,
+    /// The continuous or parent symbol (e.g. `ES.c.0` or `ES.FUT`) the instrument was
+    /// resolved through, if the metadata was requested with that symbology.
+    pub continuous_symbol: Option<Ustr>,
+    /// If the raw symbol is a multi-leg spread (e.g. the calendar spread `ESH4-ESM4`).
+    pub is_spread: bool,
+    /// The number of legs of the instrument, which is 1 for outrights.
+    pub leg_count: usize,
+    /// The asset class of the instrument, if resolved from an instrument definition record.
+    pub asset_class: Option<AssetClass>,
+    /// The instrument class of the instrument, if resolved from an instrument definition record.
+    pub instrument_class: Option<InstrumentClass>,
+}
+
+/// Returns the asset class and instrument class of the given record if it is an instrument
+/// definition, otherwise `None` for both. Definitions with an unknown `instrument_class` code
+/// also have `None` for both, so that an unrecognized code never fails the resolution.
+///
+/// The asset class is taken from the CFI code where it identifies one (such as `Index` for
+/// index futures and options), then `Equity` for OPRA options, then the default for the
+/// `instrument_class` code.
+fn definition_classes(
+    rec_ref: &dbn::RecordRef,
+    dataset: Option<Ustr>,
+) -> (Option<AssetClass>, Option<InstrumentClass>) {
+    let Some(msg) = rec_ref.get::<dbn::InstrumentDefMsg>() else {
+        return (None, None);
+    };
+
+    let (default_asset_class, instrument_class) = match parse_instrument_class(msg.instrument_class)
+    {
+        Ok(classes) => classes,
+        Err(e) => {
+            tracing::debug!("{e}, resolving without classes");
+            return (None, None);
+        }
+    };
+    let asset_class = msg
+        .cfi()
+        .ok()
+        .and_then(|cfi| parse_cfi_iso10926(cfi).ok())
+        .and_then(|(asset_class, _)| asset_class)
+        .or_else(|| {
+            dataset
+                .filter(|dataset| dataset.starts_with("OPRA"))
+                .map(|_| AssetClass::Equity)
+        })
+        .unwrap_or(default_asset_class);
+
+    (Some(asset_class), Some(instrument_class))
+}
+
+/// Returns the Databento `instrument_id` and the timestamp used to select the symbol map date
+/// for the given record.
+///
+/// The `daily_bar_offset` is subtracted from the timestamp of daily OHLCV bars, and the
+/// `eod_session_close_offset` from the timestamp of EOD OHLCV bars. If the
+/// record has no defined timestamp then the given `start` (the metadata start) is used.
+///
+/// OHLCV bars always resolve by the `ts_event` of the header (the bar open), regardless of the
+/// `ts_source`. This asymmetry is intended: bars are aggregated by Databento rather than
+/// received by a capture server, so `OhlcvMsg` has no `ts_recv`, and the bar open is the time
+/// which belongs to the bar's trading date.
+fn decode_instrument_id_and_ts(
+    rec_ref: &dbn::RecordRef,
+    ts_source: TimestampSource,
+    daily_bar_offset: u64,
+    eod_session_close_offset: u64,
+    start: u64,
+) -> Result<(u32, u64), SymbologyError> {
+    // Dispatch on the raw `rtype` once, the `instrument_id` and `ts_event` are always taken
+    // from the header so only records with a `ts_recv` need to be downcast
+    let header = rec_ref.header();
+    let ts_recv = match header.rtype {
+        dbn::rtype::MBO => record_ts_recv::<dbn::MboMsg>(rec_ref),
+        dbn::rtype::MBP_0 => record_ts_recv::<dbn::TradeMsg>(rec_ref),
+        // Also matches `tbbo` records, as a `TbboMsg` shares the `Mbp1Msg` layout and `rtype`
+        // (with the trade in the top-level fields and the BBO just before it in `levels`)
+        dbn::rtype::MBP_1 => record_ts_recv::<dbn::Mbp1Msg>(rec_ref),
+        dbn::rtype::MBP_10 => record_ts_recv::<dbn::Mbp10Msg>(rec_ref),
+        rtype if is_ohlcv_rtype(rtype) => None,
+        dbn::rtype::STATISTICS => record_ts_recv::<dbn::StatMsg>(rec_ref),
+        dbn::rtype::IMBALANCE => record_ts_recv::<dbn::ImbalanceMsg>(rec_ref),
+        // Status transitions are resolved by `ts_recv` like market data, so a halt
+        // received after midnight UTC maps to the following day's symbology
+        dbn::rtype::STATUS => record_ts_recv::<dbn::StatusMsg>(rec_ref),
+        dbn::rtype::INSTRUMENT_DEF => record_ts_recv::<dbn::InstrumentDefMsg>(rec_ref),
+        rtype => return Err(SymbologyError::UnsupportedRType { rtype }),
+    };
+
+    let ts = select_ts(
+        header,
+        ts_recv,
+        ts_source,
+        daily_bar_offset,
+        eod_session_close_offset,
+        start,
+    );
+    Ok((header.instrument_id, ts))
+}
+
+/// Returns the `ts_recv` of the record of type `R`, if the record is of that type.
+fn record_ts_recv<R: SymbologyRecord>(rec_ref: &dbn::RecordRef) -> Option<u64> {
+    rec_ref.get::<R>().and_then(SymbologyRecord::ts_recv)
+}
+
+/// Returns the timestamp used to select the symbol map date for a record with the given
+/// `header` and `ts_recv`, as for `decode_instrument_id_and_ts`.
+fn select_ts(
+    header: &dbn::RecordHeader,
+    ts_recv: Option<u64>,
+    ts_source: TimestampSource,
+    daily_bar_offset: u64,
+    eod_session_close_offset: u64,
+    start: u64,
+) -> u64 {
+    let offset = match header.rtype {
+        dbn::rtype::OHLCV_1D => daily_bar_offset,
+        // EOD bars are emitted after the session close, which may be past UTC midnight, so
+        // are resolved by their session's trading date
+        dbn::rtype::OHLCV_EOD => eod_session_close_offset,
+        _ => 0,
+    };
+
+    let ts_event = header.ts_event;
+    match (ts_source, ts_recv) {
+        // Not all records have a `ts_recv` (such as some statistics)
+        (TimestampSource::TsRecv, Some(ts_recv)) if ts_recv != dbn::UNDEF_TIMESTAMP => {
+            debug_assert!(
+                !is_ohlcv_rtype(header.rtype),
+                "OHLCV records have no `ts_recv`"
+            );
+            ts_recv
+        }
+        _ if ts_event != dbn::UNDEF_TIMESTAMP => ts_event.saturating_sub(offset),
+        _ => start,
+    }
+}
+
+/// Returns whether the given `rtype` is of an OHLCV bar record.
+fn is_ohlcv_rtype(rtype: u8) -> bool {
+    matches!(
+        rtype,
+        dbn::rtype::OHLCV_1S
+            | dbn::rtype::OHLCV_1M
+            | dbn::rtype::OHLCV_1H
+            | dbn::rtype::OHLCV_1D
+            | dbn::rtype::OHLCV_EOD
+    )
+}
+
+/// Returns the raw symbol for the Databento `instrument_id` from the symbol map for `date`.
+///
+/// Some aggregated OHLCV exports zero-fill the `instrument_id` of the record header, in which
+/// case the raw symbol can only be resolved if the symbol map has exactly one symbol.
+fn raw_symbol_for_date<'a>(
+    symbol_map: &'a PitSymbolMap,
+    instrument_id: u32,
+    publisher_id: PublisherId,
+    date: time::Date,
+) -> Result<&'a str, SymbologyError> {
+    if instrument_id == 0 && symbol_map.get(0).is_none() {
+        let symbols = symbol_map.inner();
+        return match symbols.values().next() {
+            Some(raw_symbol) if symbols.len() == 1 => Ok(raw_symbol.as_str()),
+            _ => Err(SymbologyError::ZeroInstrumentId {
+                publisher_id,
+                date,
+                symbol_count: symbols.len(),
+            }),
+        };
+    }
+
+    symbol_map
+        .get(instrument_id)
+        .map(String::as_str)
+        .ok_or(SymbologyError::MissingSymbol {
+            instrument_id,
+            publisher_id,
+            date,
+        })
+}
+
+fn ts_to_date(
+    nanoseconds: u64,
+    rtype: u8,
+    date_tz: DateTimezone,
+) -> Result<time::Date, SymbologyError> {
+    let datetime = i64::try_from(nanoseconds)
+        .ok()
+        .and_then(|nanoseconds| {
+            time::OffsetDateTime::UNIX_EPOCH.checked_add(time::Duration::nanoseconds(nanoseconds))
+        })
+        .ok_or(SymbologyError::InvalidTimestamp {
+            ts: nanoseconds,
+            rtype,
+        })?;
+    Ok(date_tz.date(datetime))
+}
+
+/// Returns the UTC offset of America/New_York at the given `datetime`, applying the US daylight
+/// saving time rules in effect since 2007 (from 2:00 local time on the second Sunday of March
+/// to 2:00 local time on the first Sunday of November).
+fn new_york_offset(datetime: time::OffsetDateTime) -> time::UtcOffset {
+    let nth_sunday = |month: time::Month, n: i64| {
+        // SAFETY: The first day of the month is always a valid date
+        let first = time::Date::from_calendar_date(datetime.year(), month, 1)
+            .expect("Invalid first day of month");
+        let days_to_sunday = i64::from((7 - first.weekday().number_days_from_sunday()) % 7);
+        first + time::Duration::days(days_to_sunday + 7 * (n - 1))
+    };
+    let dst_start =
+        nth_sunday(time::Month::March, 2).midnight().assume_utc() + time::Duration::hours(7);
+    let dst_end =
+        nth_sunday(time::Month::November, 1).midnight().assume_utc() + time::Duration::hours(6);
+
+    let hours = if (dst_start..dst_end).contains(&datetime) {
+        -4
+    } else {
+        -5
+    };
+    // SAFETY: The offsets are within the valid range
+    time::UtcOffset::from_hms(hours, 0, 0).expect("Invalid UTC offset")
+}
+
+/// Returns the given `date` clamped into the date range of the `metadata`, if it falls within
+/// one day of either boundary.
+///
+/// The first and last records of a file can be timestamped just outside the query range
+/// (e.g. one nanosecond before `start`, or exactly at the exclusive `end`), in which case the
+/// symbol map for the boundary date is used. Dates further outside the range are returned
+/// unchanged, so that building the symbol map fails.
+fn clamp_to_metadata_dates(date: time::Date, metadata: &dbn::Metadata) -> time::Date {
+    let start_date = metadata.start().date();
+    if date.next_day() == Some(start_date) {
+        tracing::trace!("Clamping date {date} to metadata start date {start_date}");
+        return start_date;
+    }
+
+    // The metadata `end` is exclusive, so the last date is that of the nanosecond before
+    if let Some(end_date) = metadata
+        .end()
+        .map(|end| (end - time::Duration::nanoseconds(1)).date())
+    {
+        if date.previous_day() == Some(end_date) {
+            tracing::trace!("Clamping date {date} to metadata end date {end_date}");
+            return end_date;
+        }
+    }
+
+    date
+}
+
+/// Returns the point-in-time symbol map of Databento `instrument_id` to raw symbol for the
+/// given `date`, choosing the mapping direction from the metadata symbology types.
+///
+/// The supported `stype_in` and `stype_out` combinations are:
+///  - Any input symbology (such as `raw_symbol`, `parent` or `continuous`) to `instrument_id`,
+///    where each mapping is from an input symbol to instrument IDs (the forward direction).
+///  - `instrument_id` to any output symbology (such as `raw_symbol`), where each mapping is from
+///    an instrument ID to output symbols (the inverse direction).
+///
+/// Some datasets reissue an `instrument_id` intraday, so more than one symbol can be mapped to
+/// the same `instrument_id` on `date`. As the mapping intervals only have a date resolution,
+/// the symbol of the last such mapping in the metadata is used, and a warning is logged.
+fn build_symbol_map(
+    metadata: &dbn::Metadata,
+    date: time::Date,
+) -> Result<PitSymbolMap, SymbologyError> {
+    match (metadata.stype_in, metadata.stype_out) {
+        (Some(dbn::SType::InstrumentId), stype_out) if stype_out != dbn::SType::InstrumentId => {
+            inverse_symbol_map(metadata, date)
+        }
+        (_, dbn::SType::InstrumentId) => {
+            let mut symbol_map = metadata.symbol_map_for_date(date)?;
+            resolve_duplicate_ids(metadata, date, &mut symbol_map);
+            Ok(symbol_map)
+        }
+        (stype_in, stype_out) => Err(SymbologyError::UnsupportedSTypes {
+            stype_in,
+            stype_out,
+        }),
+    }
+}
+
+/// Ensures the last symbol mapped to each duplicated `instrument_id` on `date` wins, logging
+/// a warning for each duplicate.
+fn resolve_duplicate_ids(
+    metadata: &dbn::Metadata,
+    date: time::Date,
+    symbol_map: &mut PitSymbolMap,
+) {
+    let mut symbols: IndexMap<u32, Vec<&str>> = IndexMap::new();
+    for mapping in &metadata.mappings {
+        let instrument_id = mapping
+            .intervals
+            .iter()
+            .find(|interval| interval.start_date <= date && date < interval.end_date)
+            .and_then(|interval| interval.symbol.parse::<u32>().ok());
+        if let Some(instrument_id) = instrument_id {
+            symbols
+                .entry(instrument_id)
+                .or_default()
+                .push(mapping.raw_symbol.as_str());
+        }
+    }
+
+    for (instrument_id, raw_symbols) in symbols {
+        if let [.., _, last] = raw_symbols.as_slice() {
+            tracing::warn!(
+                "Duplicate `instrument_id` {instrument_id} on {date} for raw symbols \
+                 {raw_symbols:?}, using last '{last}'"
+            );
+            symbol_map
+                .inner_mut()
+                .insert(instrument_id, (*last).to_string());
+        }
+    }
+}
+
+fn inverse_symbol_map(
+    metadata: &dbn::Metadata,
+    date: time::Date,
+) -> Result<PitSymbolMap, SymbologyError> {
+    let start_date = metadata.start().date();
+    let end_date = metadata
+        .end()
+        .map(|end| (end - time::Duration::nanoseconds(1)).date());
+    if date < start_date || end_date.is_some_and(|end_date| date > end_date) {
+        return Err(dbn::Error::bad_arg("date", "date is outside the query range").into());
+    }
+
+    let mut symbol_map = PitSymbolMap::new();
+    for mapping in &metadata.mappings {
+        let Some(interval) = mapping
+            .intervals
+            .iter()
+            .find(|interval| interval.start_date <= date && date < interval.end_date)
+        else {
+            continue;
+        };
+        if interval.symbol.is_empty() {
+            continue; // Not mapped on this date
+        }
+        let instrument_id = mapping.raw_symbol.parse::<u32>().map_err(|_| {
+            dbn::Error::bad_arg(
+                "metadata",
+                format!(
+                    "invalid `instrument_id` '{}' in mappings",
+                    mapping.raw_symbol
+                ),
+            )
+        })?;
+        symbol_map
+            .inner_mut()
+            .insert(instrument_id, interval.symbol.clone());
+    }
+    Ok(symbol_map)
+}
+
+/// Represents the options for resolving a Nautilus `InstrumentId` from a Databento raw symbol,
+/// beyond the venue maps. The defaults apply no aliases, overrides or fallback venue.
+#[derive(Clone, Copy, Default)]
+struct ResolveOptions<'a> {
+    symbol_aliases: Option<&'a HashMap<Ustr, Ustr>>,
+    venue_policy: VenueResolutionPolicy,
+    fallback_venue: Option<Venue>,
+    /// Resolves the venue of a symbol ahead of the venue maps (such as venue overrides).
+    venue_resolver: Option<&'a dyn Fn(&Symbol, PublisherId) -> Option<Venue>>,
+}
+
+fn resolve_instrument_id(
+    raw_symbol: Ustr,
+    instrument_id: Option<u32>,
+    publisher_id: PublisherId,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &impl GlbxVenueMap,
+    options: &ResolveOptions,
+) -> Result<InstrumentId, SymbologyError> {
+    let symbol = resolve_symbol(raw_symbol, publisher_id, options.symbol_aliases)?;
+    let venue = resolve_symbol_venue(
+        &symbol,
+        instrument_id,
+        publisher_id,
+        publisher_venue_map,
+        glbx_exchange_map,
+        options,
+    )?;
+    Ok(InstrumentId::new(symbol, venue))
+}
+
+/// Returns the Nautilus `Symbol` for the given Databento `raw_symbol`, validated and with any
+/// alias applied.
+fn resolve_symbol(
+    raw_symbol: Ustr,
+    publisher_id: PublisherId,
+    symbol_aliases: Option<&HashMap<Ustr, Ustr>>,
+) -> Result<Symbol, SymbologyError> {
+    let raw_symbol = validate_raw_symbol(raw_symbol, publisher_id)?;
+    if is_opra_publisher(publisher_id) && !is_osi_symbol(raw_symbol.as_str()) {
+        return Err(SymbologyError::InvalidOsiSymbol {
+            raw_symbol,
+            publisher_id,
+        });
+    }
+    Ok(Symbol {
+        value: symbol_aliases
+            .and_then(|aliases| aliases.get(&raw_symbol).copied())
+            .unwrap_or(raw_symbol),
+    })
+}
+
+/// Returns the venue for the given `symbol` and `publisher_id`, from the venue resolver of the
+/// `options`, then the venue maps in the order of the venue policy, then the fallback venue.
+fn resolve_symbol_venue(
+    symbol: &Symbol,
+    instrument_id: Option<u32>,
+    publisher_id: PublisherId,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: &impl GlbxVenueMap,
+    options: &ResolveOptions,
+) -> Result<Venue, SymbologyError> {
+    if let Some(venue) = options
+        .venue_resolver
+        .and_then(|venue_resolver| venue_resolver(symbol, publisher_id))
+    {
+        return Ok(venue);
+    }
+
+    let glbx_venue = || glbx_exchange_map.glbx_venue(symbol, publisher_id);
+    let publisher_venue = || publisher_venue_map.get(&publisher_id).copied();
+    let venue = match options.venue_policy {
+        VenueResolutionPolicy::GlbxFirst => glbx_venue().or_else(publisher_venue),
+        VenueResolutionPolicy::PublisherFirst => publisher_venue().or_else(glbx_venue),
+        VenueResolutionPolicy::GlbxOnlyForGlbxPublishers => is_glbx_publisher(publisher_id)
+            .then(glbx_venue)
+            .flatten()
+            .or_else(publisher_venue),
+    };
+
+    let Some(venue) = venue else {
+        warn_unknown_publisher(publisher_id);
+        if let Some(fallback_venue) = options.fallback_venue {
+            tracing::debug!(
+                "Using fallback venue {fallback_venue} for {symbol} with `publisher_id` {publisher_id}"
+            );
+            return Ok(fallback_venue);
+        }
+        return Err(SymbologyError::UnknownPublisher {
+            publisher_id,
+            instrument_id,
+        });
+    };
+
+    Ok(venue)
+}
+
+/// Returns whether the given `publisher_id` is a CME Globex publisher.
+fn is_glbx_publisher(publisher_id: PublisherId) -> bool {
+    is_dataset_publisher(publisher_id, &[GLBX_DATASET])
+}
+
+/// Returns the exchange venue for the given CME Globex `symbol`, falling back to its product
+/// root (e.g. `ES` for `ESH4`) so that every contract month doesn't need its own entry.
+fn glbx_exchange_venue<'a, V>(
+    symbol: &Symbol,
+    glbx_exchange_map: &'a HashMap<Symbol, V>,
+) -> Option<&'a V> {
+    glbx_exchange_map.get(symbol).or_else(|| {
+        let root = glbx_product_root(symbol.value.as_str())?;
+        glbx_exchange_map.get(&Symbol::from_str_unchecked(root))
+    })
+}
+
+/// Provides the GLBX exchange map venue of a symbol for a publisher.
+trait GlbxVenueMap {
+    fn glbx_venue(&self, symbol: &Symbol, publisher_id: PublisherId) -> Option<Venue>;
+}
+
+impl GlbxVenueMap for HashMap<Symbol, Venue> {
+    fn glbx_venue(&self, symbol: &Symbol, _publisher_id: PublisherId) -> Option<Venue> {
+        glbx_exchange_venue(symbol, self).copied()
+    }
+}
+
+impl GlbxVenueMap for HashMap<Symbol, GlbxVenueEntry> {
+    fn glbx_venue(&self, symbol: &Symbol, publisher_id: PublisherId) -> Option<Venue> {
+        glbx_exchange_venue(symbol, self).map(|entry| entry.venue(publisher_id))
+    }
+}
+
+/// Returns the number of legs for the given `raw_symbol`, where spread legs are separated by
+/// `-` (e.g. `ESH4-ESM4`) and each leg is at least two alphanumeric characters, so that
+/// symbols such as `BRK-B` are not treated as spreads.
+fn spread_leg_count(raw_symbol: &str) -> usize {
+    let is_leg = |leg: &str| leg.len() >= 2 && leg.bytes().all(|b| b.is_ascii_alphanumeric());
+    if raw_symbol.contains('-') && raw_symbol.split('-').all(is_leg) {
+        raw_symbol.split('-').count()
+    } else {
+        1
+    }
+}
+
+/// Returns whether the given `publisher_id` is a known publisher of one of the given `datasets`.
+fn is_dataset_publisher(publisher_id: PublisherId, datasets: &[&str]) -> bool {
+    dbn::Publisher::try_from(publisher_id)
+        .is_ok_and(|publisher| datasets.contains(&publisher.dataset().as_str()))
+}
+
+/// Returns whether the given `publisher_id` is an OPRA publisher, whose raw symbols are
+/// OSI option symbols.
+fn is_opra_publisher(publisher_id: PublisherId) -> bool {
+    is_dataset_publisher(publisher_id, &[OPRA_DATASET])
+}
+
+/// Returns whether the given `publisher_id` is a US equity publisher.
+fn is_equity_publisher(publisher_id: PublisherId) -> bool {
+    is_dataset_publisher(publisher_id, &EQUITY_DATASETS)
+}
+
+/// Returns whether the given `raw_symbol` is an OSI option symbol, which is the root symbol
+/// padded to 6 characters, the expiration date (`YYMMDD`), `C` or `P`, and the strike price
+/// in thousandths padded to 8 digits (e.g. `AAPL  240119C00150000`).
+fn is_osi_symbol(raw_symbol: &str) -> bool {
+    let bytes = raw_symbol.as_bytes();
+    if bytes.len() != OSI_SYMBOL_LEN {
+        return false;
+    }
+
+    let (root, rest) = bytes.split_at(6);
+    let root_len = root.iter().take_while(|b| **b != b' ').count();
+    root_len > 0
+        && root[..root_len].iter().all(u8::is_ascii_alphanumeric)
+        && root[root_len..].iter().all(|b| *b == b' ')
+        && rest[..6].iter().all(u8::is_ascii_digit)
+        && matches!(rest[6], b'C' | b'P')
+        && rest[7..].iter().all(u8::is_ascii_digit)
+}
+
+/// Returns the given `raw_symbol` with any trailing null padding from fixed-width DBN symbol
+/// fields removed, or an error if it is empty or whitespace-only.
+fn validate_raw_symbol(
+    raw_symbol: Ustr,
+    publisher_id: PublisherId,
+) -> Result<Ustr, SymbologyError> {
+    let trimmed = raw_symbol.trim_end_matches('\0');
+    if trimmed.trim().is_empty() {
+        return Err(SymbologyError::InvalidSymbol { publisher_id });
+    }
+
+    if trimmed.len() == raw_symbol.len() {
+        Ok(raw_symbol)
+    } else {
+        Ok(Ustr::from(trimmed))
+    }
+}
+
+/// Logs a warning for the given unknown `publisher_id`, only once per process.
+///
+/// Returns whether a warning was logged.
+fn warn_unknown_publisher(publisher_id: PublisherId) -> bool {
+    let is_unseen = WARNED_PUBLISHER_IDS
+        .lock()
+        .is_ok_and(|mut warned| warned.insert(publisher_id));
+    if is_unseen {
+        tracing::warn!("No venue found for `publisher_id` {publisher_id}, extend the venue map");
+    }
+    is_unseen
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use time::{Date, Month};
+
+    use super::*;
+    use crate::databento::stubs::{
+        make_date, make_imbalance_record, make_ohlcv_record, make_stat_record, make_status_record,
+        make_trade_record, TS_2024_01_02,
+    };
+
+    #[rstest]
+    fn test_warn_unknown_publisher_only_once() {
+        // Publisher ID not used by any other test, as warnings are tracked per process
+        let publisher_id = 65_000;
+
+        assert!(warn_unknown_publisher(publisher_id));
+        assert!(!warn_unknown_publisher(publisher_id));
+        assert!(!warn_unknown_publisher(publisher_id));
+    }
+
+    // The previous `RType` enum dispatch, kept to verify the header-based dispatch against it
+    fn decode_instrument_id_and_ts_by_rtype_enum(
+        rec_ref: &dbn::RecordRef,
+        ts_source: TimestampSource,
+        daily_bar_offset: u64,
+        start: u64,
+    ) -> Option<(u32, u64)> {
+        let mut offset = 0;
+        let (instrument_id, ts_recv) = match rec_ref.rtype().ok()? {
+            dbn::RType::Mbo => {
+                let msg = rec_ref.get::<dbn::MboMsg>()?;
+                (msg.hd.instrument_id, Some(msg.ts_recv))
+            }
+            dbn::RType::Mbp0 => {
+                let msg = rec_ref.get::<dbn::TradeMsg>()?;
+                (msg.hd.instrument_id, Some(msg.ts_recv))
+            }
+            dbn::RType::Ohlcv1S | dbn::RType::Ohlcv1M | dbn::RType::Ohlcv1H => {
+                let msg = rec_ref.get::<dbn::OhlcvMsg>()?;
+                (msg.hd.instrument_id, None)
+            }
+            dbn::RType::Ohlcv1D | dbn::RType::OhlcvEod => {
+                let msg = rec_ref.get::<dbn::OhlcvMsg>()?;
+                offset = daily_bar_offset;
+                (msg.hd.instrument_id, None)
+            }
+            dbn::RType::Statistics => {
+                let msg = rec_ref.get::<dbn::StatMsg>()?;
+                (msg.hd.instrument_id, Some(msg.ts_recv))
+            }
+            dbn::RType::Imbalance => {
+                let msg = rec_ref.get::<dbn::ImbalanceMsg>()?;
+                (msg.hd.instrument_id, Some(msg.ts_recv))
+            }
+            dbn::RType::Status => {
+                let msg = rec_ref.get::<dbn::StatusMsg>()?;
+                (msg.hd.instrument_id, Some(msg.ts_recv))
+            }
+            _ => return None,
+        };
+
+        let ts_event = rec_ref.header().ts_event;
+        let ts = match (ts_source, ts_recv) {
+            (TimestampSource::TsRecv, Some(ts_recv)) if ts_recv != dbn::UNDEF_TIMESTAMP => ts_recv,
+            _ if ts_event != dbn::UNDEF_TIMESTAMP => ts_event.saturating_sub(offset),
+            _ => start,
+        };
+        Some((instrument_id, ts))
+    }
+
+    #[rstest]
+    fn test_decode_instrument_id_and_ts_matches_rtype_enum_dispatch(
+        #[values(TimestampSource::TsRecv, TimestampSource::TsEvent)] ts_source: TimestampSource,
+    ) {
+        let one_hour_ns = 3_600_000_000_000;
+        let trade = make_trade_record(5602, 1, TS_2024_01_02 + 1);
+        let stat = make_stat_record(5603, 1, TS_2024_01_02, dbn::UNDEF_TIMESTAMP);
+        let imbalance = make_imbalance_record(5604, 1, TS_2024_01_02 + 2);
+        let status = make_status_record(5605, 1, TS_2024_01_02, TS_2024_01_02 + 3);
+        let ohlcv_1m = make_ohlcv_record(dbn::rtype::OHLCV_1M, 5606, 1, TS_2024_01_02 + 4);
+        let ohlcv_1d =
+            make_ohlcv_record(dbn::rtype::OHLCV_1D, 5607, 1, TS_2024_01_02 + one_hour_ns);
+        let ohlcv_eod = make_ohlcv_record(dbn::rtype::OHLCV_EOD, 0, 1, dbn::UNDEF_TIMESTAMP);
+        let error = dbn::ErrorMsg::new(TS_2024_01_02, "Test error");
+        let records = [
+            dbn::RecordRef::from(&trade),
+            dbn::RecordRef::from(&stat),
+            dbn::RecordRef::from(&imbalance),
+            dbn::RecordRef::from(&status),
+            dbn::RecordRef::from(&ohlcv_1m),
+            dbn::RecordRef::from(&ohlcv_1d),
+            dbn::RecordRef::from(&ohlcv_eod),
+            dbn::RecordRef::from(&error),
+        ];
+
+        for rec_ref in &records {
+            let expected =
+                decode_instrument_id_and_ts_by_rtype_enum(rec_ref, ts_source, one_hour_ns, 1);
+            let result =
+                decode_instrument_id_and_ts(rec_ref, ts_source, one_hour_ns, one_hour_ns, 1).ok();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[rstest]
+    #[case("ESH4", 1)]
+    #[case("ESH4-ESM4", 2)]
+    #[case("GEH4-GEM4-GEU4", 3)]
+    #[case("BRK-B", 1)]
+    #[case("ESH4-", 1)]
+    fn test_spread_leg_count(#[case] raw_symbol: &str, #[case] expected: usize) {
+        assert_eq!(spread_leg_count(raw_symbol), expected);
+    }
+
+    #[rstest]
+    #[case::est_before_midnight(1_704_257_940, make_date(2))] // 2024-01-03T04:59:00Z
+    #[case::est_after_midnight(1_704_258_000, make_date(3))] // 2024-01-03T05:00:00Z
+    #[case::edt_before_midnight(1_719_892_740, Date::from_calendar_date(2024, Month::July, 1).unwrap())] // 2024-07-02T03:59:00Z
+    #[case::edt_after_midnight(1_719_892_800, Date::from_calendar_date(2024, Month::July, 2).unwrap())] // 2024-07-02T04:00:00Z
+    fn test_ts_to_date_new_york(#[case] seconds: u64, #[case] expected: Date) {
+        let date = ts_to_date(
+            seconds * 1_000_000_000,
+            dbn::rtype::MBP_0,
+            DateTimezone::NewYork,
+        )
+        .unwrap();
+
+        assert_eq!(date, expected);
+    }
+}
@@ -0,0 +1,266 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+use std::collections::HashMap;
+
+use databento::dbn::{PitSymbolMap, Record};
+use indexmap::IndexMap;
+use nautilus_model::identifiers::{instrument_id::InstrumentId, symbol::Symbol, venue::Venue};
+use ustr::Ustr;
+
+use super::{
+    build_symbol_map, clamp_to_metadata_dates, decode_instrument_id_and_ts, raw_symbol_for_date,
+    resolve_instrument_id, ts_to_date, DatabentoSymbologyResolver, DateTimezone, ResolveOptions,
+    SymbologyError, TimestampSource,
+};
+use crate::databento::{
+    types::PublisherId,
+    venues::{normalize_glbx_exchange_map, normalize_publisher_venue_map},
+};
+
+/// Provides Databento symbology resolution for records from multiple datasets in one session.
+///
+/// Records are routed to the metadata for their dataset by `publisher_id`, as the same raw
+/// symbol (and Databento `instrument_id`) can exist in more than one dataset. The venue maps are
+/// shared between all datasets.
+pub struct MultiDatasetResolver {
+    metadata: HashMap<String, dbn::Metadata>,
+    publisher_venue_map: IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: HashMap<Symbol, Venue>,
+    symbol_maps: HashMap<String, IndexMap<time::Date, PitSymbolMap>>,
+}
+
+impl MultiDatasetResolver {
+    #[must_use]
+    pub fn new(
+        publisher_venue_map: IndexMap<PublisherId, Venue>,
+        glbx_exchange_map: HashMap<Symbol, Venue>,
+    ) -> Self {
+        Self {
+            metadata: HashMap::new(),
+            publisher_venue_map: normalize_publisher_venue_map(publisher_venue_map),
+            glbx_exchange_map: normalize_glbx_exchange_map(glbx_exchange_map),
+            symbol_maps: HashMap::new(),
+        }
+    }
+
+    /// Insert the metadata for its dataset, replacing (and invalidating the cached symbol maps
+    /// of) any existing metadata for that dataset.
+    pub fn insert_metadata(&mut self, metadata: dbn::Metadata) {
+        self.symbol_maps.remove(&metadata.dataset);
+        self.metadata.insert(metadata.dataset.clone(), metadata);
+    }
+
+    /// Return the metadata for the given `dataset` (if found).
+    #[must_use]
+    pub fn metadata(&self, dataset: &str) -> Option<&dbn::Metadata> {
+        self.metadata.get(dataset)
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given record, using the metadata for the
+    /// dataset of the record's `publisher_id`.
+    pub fn resolve(&mut self, rec_ref: &dbn::RecordRef) -> Result<InstrumentId, SymbologyError> {
+        let header = rec_ref.header();
+        let publisher_id = header.publisher_id;
+        let publisher = dbn::Publisher::try_from(publisher_id).map_err(|_| {
+            SymbologyError::UnknownPublisher {
+                publisher_id,
+                instrument_id: Some(header.instrument_id),
+            }
+        })?;
+        let dataset = publisher.dataset().as_str();
+        let metadata =
+            self.metadata
+                .get(dataset)
+                .ok_or_else(|| SymbologyError::MissingMetadata {
+                    dataset: dataset.to_string(),
+                    publisher_id,
+                })?;
+
+        let (instrument_id, nanoseconds) =
+            decode_instrument_id_and_ts(rec_ref, TimestampSource::default(), 0, 0, metadata.start)?;
+        let date = clamp_to_metadata_dates(
+            ts_to_date(nanoseconds, header.rtype, DateTimezone::Utc)?,
+            metadata,
+        );
+        let symbol_maps = self.symbol_maps.entry(dataset.to_string()).or_default();
+        let symbol_map = match symbol_maps.entry(date) {
+            indexmap::map::Entry::Occupied(entry) => entry.into_mut(),
+            indexmap::map::Entry::Vacant(entry) => entry.insert(build_symbol_map(metadata, date)?),
+        };
+        let raw_symbol = raw_symbol_for_date(symbol_map, instrument_id, publisher_id, date)?;
+
+        resolve_instrument_id(
+            Ustr::from(raw_symbol),
+            Some(instrument_id),
+            publisher_id,
+            &self.publisher_venue_map,
+            &self.glbx_exchange_map,
+            &ResolveOptions::default(),
+        )
+    }
+}
+
+/// Provides Databento symbology resolution for records spanning an ordered list of metadata
+/// segments.
+///
+/// Each record is resolved with the segment whose `[start, end)` range contains its timestamp,
+/// which disambiguates Databento `instrument_id`s reused for different symbols over time (such
+/// as across a contract roll). A segment without an `end` contains all later timestamps.
+pub struct SegmentedSymbologyResolver {
+    segments: Vec<DatabentoSymbologyResolver>,
+}
+
+impl SegmentedSymbologyResolver {
+    #[must_use]
+    pub fn new(
+        segments: Vec<dbn::Metadata>,
+        publisher_venue_map: &IndexMap<PublisherId, Venue>,
+        glbx_exchange_map: &HashMap<Symbol, Venue>,
+    ) -> Self {
+        let segments = segments
+            .into_iter()
+            .map(|metadata| {
+                DatabentoSymbologyResolver::new(
+                    metadata,
+                    publisher_venue_map.clone(),
+                    glbx_exchange_map.clone(),
+                )
+            })
+            .collect();
+        Self { segments }
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given record, using the metadata segment
+    /// which contains the record's timestamp.
+    pub fn resolve(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+        publisher_id: PublisherId,
+    ) -> Result<InstrumentId, SymbologyError> {
+        let start = self
+            .segments
+            .first()
+            .map_or(0, |segment| segment.metadata().start);
+        let (_, ts) =
+            decode_instrument_id_and_ts(rec_ref, TimestampSource::default(), 0, 0, start)?;
+        let segment = self
+            .segments
+            .iter_mut()
+            .find(|segment| {
+                let metadata = segment.metadata();
+                metadata.start <= ts && metadata.end.map_or(true, |end| ts < end.get())
+            })
+            .ok_or(SymbologyError::MissingSegment { ts })?;
+
+        segment.resolve(rec_ref, publisher_id)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use rstest::rstest;
+
+    use super::*;
+    use crate::databento::stubs::{
+        make_date, make_esh4_metadata, make_publisher_venue_map, make_trade_record, TS_2024_01_02,
+    };
+
+    #[rstest]
+    fn test_multi_dataset_resolver_routes_by_publisher() {
+        let mut xnas_metadata = make_esh4_metadata();
+        xnas_metadata.dataset = "XNAS.ITCH".to_string();
+        xnas_metadata.mappings[0].raw_symbol = "AAPL".to_string();
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX()), (2, Venue::from("XNAS"))]);
+        let mut resolver = MultiDatasetResolver::new(publisher_venue_map, HashMap::new());
+        resolver.insert_metadata(make_esh4_metadata());
+        resolver.insert_metadata(xnas_metadata);
+
+        // Same Databento `instrument_id` in both datasets
+        let glbx_msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let xnas_msg = make_trade_record(5602, 2, TS_2024_01_02);
+
+        let glbx_instrument_id = resolver.resolve(&dbn::RecordRef::from(&glbx_msg)).unwrap();
+        let xnas_instrument_id = resolver.resolve(&dbn::RecordRef::from(&xnas_msg)).unwrap();
+
+        assert_eq!(glbx_instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(xnas_instrument_id, InstrumentId::from("AAPL.XNAS"));
+    }
+
+    #[rstest]
+    fn test_multi_dataset_resolver_with_missing_metadata_returns_err() {
+        let mut resolver = MultiDatasetResolver::new(make_publisher_venue_map(), HashMap::new());
+        resolver.insert_metadata(make_esh4_metadata());
+        let msg = make_trade_record(5602, 2, TS_2024_01_02);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg));
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::MissingMetadata { dataset, publisher_id: 2 } if dataset == "XNAS.ITCH"
+        ));
+    }
+
+    #[rstest]
+    fn test_segmented_resolver_resolves_reused_id_by_timestamp() {
+        let one_day_ns = 86_400_000_000_000;
+        let mut segment1 = make_esh4_metadata();
+        segment1.end = NonZeroU64::new(TS_2024_01_02 + one_day_ns); // 2024-01-03T00:00:00Z
+        segment1.mappings[0].intervals[0].end_date = make_date(3);
+        let mut segment2 = make_esh4_metadata();
+        segment2.start = TS_2024_01_02 + one_day_ns;
+        segment2.end = NonZeroU64::new(TS_2024_01_02 + 3 * one_day_ns); // 2024-01-05T00:00:00Z
+        segment2.mappings[0].raw_symbol = "ESM4".to_string();
+        segment2.mappings[0].intervals[0].start_date = make_date(3);
+        segment2.mappings[0].intervals[0].end_date = make_date(5);
+        let mut resolver = SegmentedSymbologyResolver::new(
+            vec![segment1, segment2],
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+        let msg1 = make_trade_record(5602, 1, TS_2024_01_02);
+        let msg2 = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+
+        let instrument_id1 = resolver.resolve(&dbn::RecordRef::from(&msg1), 1).unwrap();
+        let instrument_id2 = resolver.resolve(&dbn::RecordRef::from(&msg2), 1).unwrap();
+
+        assert_eq!(instrument_id1, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(instrument_id2, InstrumentId::from("ESM4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_segmented_resolver_with_no_segment_returns_err() {
+        let mut resolver = SegmentedSymbologyResolver::new(
+            vec![make_esh4_metadata()],
+            &make_publisher_venue_map(),
+            &HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, 1_704_326_400_000_000_000); // 2024-01-04T00:00:00Z
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 1);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::MissingSegment {
+                ts: 1_704_326_400_000_000_000
+            }
+        ));
+    }
+}
@@ -0,0 +1,3455 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use databento::dbn::{PitSymbolMap, Record};
+use indexmap::IndexMap;
+use nautilus_model::identifiers::{instrument_id::InstrumentId, symbol::Symbol, venue::Venue};
+use serde::Deserialize;
+use ustr::Ustr;
+
+use super::{
+    build_symbol_map, clamp_to_metadata_dates, dataset_for_publisher, decode_instrument_id_and_ts,
+    definition_classes, format_instrument_id, is_dataset_publisher, is_equity_publisher,
+    raw_symbol_for_date, resolve_instrument_id, select_ts, spread_leg_count, ts_to_date,
+    DateTimezone, IdFormat, ResolveOptions, ResolvedInstrument, ShareClassSeparator,
+    SymbologyError, TimestampSource, VenueResolutionPolicy, SHARE_CLASS_SEPARATORS,
+};
+use crate::databento::{
+    types::PublisherId,
+    venues::{
+        build_publisher_venue_map, default_glbx_exchange_map, normalize_glbx_exchange_map,
+        normalize_publisher_venue_map, normalize_symbol, normalize_venue, GlbxVenueEntry,
+    },
+};
+
+const DEFAULT_SYMBOL_MAP_CAPACITY: usize = 8;
+
+/// Represents a Databento control record (error or system message) received in a stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControlMsg {
+    /// An error message from the gateway (`ErrorMsg`).
+    Error { message: String },
+    /// A heartbeat system message, sent when no data has been published for a while.
+    Heartbeat,
+    /// Any other system message (`SystemMsg`), such as a subscription acknowledgement.
+    System { message: String },
+}
+
+impl ControlMsg {
+    /// Classify the given record as a control message, returning `None` for records which
+    /// are not error or system messages.
+    #[must_use]
+    pub fn from_record(rec_ref: &dbn::RecordRef) -> Option<Self> {
+        if let Some(msg) = rec_ref.get::<dbn::ErrorMsg>() {
+            let message = msg.err().unwrap_or_default().to_string();
+            return Some(Self::Error { message });
+        }
+        if let Some(msg) = rec_ref.get::<dbn::SystemMsg>() {
+            let message = msg.msg().unwrap_or_default();
+            if message == "Heartbeat" {
+                return Some(Self::Heartbeat);
+            }
+            return Some(Self::System {
+                message: message.to_string(),
+            });
+        }
+        None
+    }
+}
+
+/// Represents the outcome of resolving a record which may be a control record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResolveOutcome {
+    /// The record was a data record, resolved to the given instrument ID.
+    Instrument(InstrumentId),
+    /// The record was a control record, which carries no instrument.
+    Control(ControlMsg),
+}
+
+/// Provides counters of symbology resolutions by `publisher_id` and `rtype`, and of errors by
+/// category (see `SymbologyError::kind`).
+#[derive(Clone, Debug, Default)]
+pub struct ResolverMetrics {
+    resolved: HashMap<(PublisherId, u8), u64>,
+    errors: HashMap<&'static str, u64>,
+}
+
+/// Represents a point-in-time copy of the `ResolverMetrics` counters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolverMetricsSnapshot {
+    /// The count of successful resolutions by `publisher_id` and `rtype`.
+    pub resolved: HashMap<(PublisherId, u8), u64>,
+    /// The count of failed resolutions by error category.
+    pub errors: HashMap<&'static str, u64>,
+}
+
+impl ResolverMetrics {
+    pub fn record_resolved(&mut self, publisher_id: PublisherId, rtype: u8) {
+        *self.resolved.entry((publisher_id, rtype)).or_default() += 1;
+    }
+
+    pub fn record_error(&mut self, error: &SymbologyError) {
+        *self.errors.entry(error.kind()).or_default() += 1;
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> ResolverMetricsSnapshot {
+        ResolverMetricsSnapshot {
+            resolved: self.resolved.clone(),
+            errors: self.errors.clone(),
+        }
+    }
+}
+
+/// Provides the timestamps of a DBN record type which select its symbol map date, so that
+/// owned records can be resolved without a `RecordRef`.
+pub trait SymbologyRecord: dbn::Record + dbn::HasRType {
+    /// Return the capture-server-received timestamp, or `None` if the record type has none.
+    fn ts_recv(&self) -> Option<u64>;
+}
+
+impl SymbologyRecord for dbn::MboMsg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+impl SymbologyRecord for dbn::TradeMsg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+impl SymbologyRecord for dbn::Mbp1Msg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+impl SymbologyRecord for dbn::Mbp10Msg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+impl SymbologyRecord for dbn::OhlcvMsg {
+    // Bars are aggregated by Databento rather than received (see `decode_instrument_id_and_ts`)
+    fn ts_recv(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl SymbologyRecord for dbn::StatMsg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+impl SymbologyRecord for dbn::ImbalanceMsg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+impl SymbologyRecord for dbn::StatusMsg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+impl SymbologyRecord for dbn::InstrumentDefMsg {
+    fn ts_recv(&self) -> Option<u64> {
+        Some(self.ts_recv)
+    }
+}
+
+/// Provides the point-in-time symbol map of Databento `instrument_id` to raw symbol for a date.
+///
+/// This is implemented for `dbn::Metadata`, and allows the resolution logic of a
+/// `DatabentoSymbologyResolver` to be reused with symbol maps from other sources.
+pub trait SymbolMapProvider {
+    /// Return the symbol map for the given `date`.
+    fn symbol_map_for_date(&self, date: time::Date) -> Result<PitSymbolMap, SymbologyError>;
+}
+
+impl SymbolMapProvider for dbn::Metadata {
+    fn symbol_map_for_date(&self, date: time::Date) -> Result<PitSymbolMap, SymbologyError> {
+        build_symbol_map(self, date)
+    }
+}
+
+/// Represents a Databento symbology resolution JSON export (as returned by the historical
+/// `symbology.resolve` endpoint).
+#[derive(Deserialize)]
+struct SymbologyJson {
+    result: IndexMap<String, Vec<SymbologyJsonInterval>>,
+    stype_in: String,
+    stype_out: String,
+    start_date: String,
+    end_date: String,
+}
+
+/// Represents a date range mapping of a symbology JSON export, where `d1` is exclusive.
+#[derive(Deserialize)]
+struct SymbologyJsonInterval {
+    d0: String,
+    d1: String,
+    s: String,
+}
+
+/// Parse a `YYYY-MM-DD` date of a symbology JSON export.
+fn parse_symbology_date(value: &str) -> anyhow::Result<time::Date> {
+    let mut parts = value.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        anyhow::bail!("Invalid symbology date '{value}', expected YYYY-MM-DD");
+    };
+    let month = time::Month::try_from(month.parse::<u8>()?)?;
+    Ok(time::Date::from_calendar_date(
+        year.parse()?,
+        month,
+        day.parse()?,
+    )?)
+}
+
+fn unix_nanos_at_midnight(date: time::Date) -> anyhow::Result<u64> {
+    Ok(u64::try_from(
+        date.midnight().assume_utc().unix_timestamp_nanos(),
+    )?)
+}
+
+/// Provides Databento symbology resolution for the records of a single DBN file.
+///
+/// The symbol map for each date is built once from the metadata (or the symbol map provider,
+/// if any) and then cached, as consecutive records will generally share the same date. The
+/// venues and symbols of the venue maps are normalized on construction (see
+/// `venues::normalize_venue`).
+pub struct DatabentoSymbologyResolver<P = dbn::Metadata> {
+    metadata: dbn::Metadata,
+    symbol_map_provider: Option<P>,
+    publisher_venue_map: IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: HashMap<Symbol, GlbxVenueEntry>,
+    venue_overrides: HashMap<(PublisherId, Symbol), Venue>,
+    symbol_maps: IndexMap<time::Date, PitSymbolMap>,
+    symbol_map_capacity: usize,
+    symbol_map_hits: u64,
+    interned_symbols: HashMap<u32, Ustr>,
+    interned_symbols_date: Option<time::Date>,
+    symbol_interns: u64,
+    raw_symbols: HashMap<u32, Ustr>,
+    latest_symbols: Option<HashMap<u32, Ustr>>,
+    symbol_aliases: HashMap<Ustr, Ustr>,
+    share_class_separator: Option<ShareClassSeparator>,
+    ts_source: TimestampSource,
+    daily_bar_offset: u64,
+    eod_session_close_offset: Option<u64>,
+    pre_start_tolerance: Option<u64>,
+    zero_ts_as_start: bool,
+    prior_map_window: Option<u32>,
+    date_tz: DateTimezone,
+    skip_unsupported: bool,
+    rtype_filter: Vec<u8>,
+    strict_venues: bool,
+    strict_publishers: bool,
+    check_header_publisher: bool,
+    instrument_ids: Option<IndexMap<(u32, PublisherId), InstrumentId>>,
+    instrument_ids_date: Option<time::Date>,
+    venue_policy: VenueResolutionPolicy,
+    fallback_venue: Option<Venue>,
+    metrics: Option<ResolverMetrics>,
+    on_new_instrument: Option<Box<dyn FnMut(&InstrumentId) + Send>>,
+    distinct_instruments: HashSet<InstrumentId>,
+    #[cfg(feature = "latency-histogram")]
+    latency: hdrhistogram::Histogram<u64>,
+}
+
+impl DatabentoSymbologyResolver {
+    #[must_use]
+    pub fn new(
+        metadata: dbn::Metadata,
+        publisher_venue_map: IndexMap<PublisherId, Venue>,
+        glbx_exchange_map: HashMap<Symbol, Venue>,
+    ) -> Self {
+        Self::from_parts(metadata, None, publisher_venue_map, glbx_exchange_map)
+    }
+
+    /// Return a builder for a resolver for the given `metadata`.
+    #[must_use]
+    pub fn builder(metadata: dbn::Metadata) -> DatabentoSymbologyResolverBuilder {
+        DatabentoSymbologyResolverBuilder::new(metadata)
+    }
+
+    /// Create a resolver from a standalone Databento symbology JSON export, for resolving
+    /// records without the metadata of their DBN file.
+    ///
+    /// The date range mappings of the export are kept as metadata symbol mappings, so the
+    /// symbol map for each date is only built when a record of that date is resolved. The
+    /// bundled publisher venue and GLBX exchange maps are used, and as the export has no
+    /// dataset, dataset checks (such as strict publishers) do not apply.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid, or has invalid dates or symbology types.
+    pub fn from_symbology_json(json: &str) -> anyhow::Result<Self> {
+        let export: SymbologyJson = serde_json::from_str(json)?;
+        let mut mappings = Vec::with_capacity(export.result.len());
+        for (raw_symbol, intervals) in export.result {
+            let intervals = intervals
+                .into_iter()
+                .map(|interval| {
+                    Ok(dbn::MappingInterval {
+                        start_date: parse_symbology_date(&interval.d0)?,
+                        end_date: parse_symbology_date(&interval.d1)?,
+                        symbol: interval.s,
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            mappings.push(dbn::SymbolMapping {
+                raw_symbol,
+                intervals,
+            });
+        }
+
+        let start = unix_nanos_at_midnight(parse_symbology_date(&export.start_date)?)?;
+        let end = unix_nanos_at_midnight(parse_symbology_date(&export.end_date)?)?;
+        let metadata = dbn::Metadata::builder()
+            .dataset(String::new())
+            .schema(None)
+            .start(start)
+            .end(std::num::NonZeroU64::new(end))
+            .stype_in(Some(export.stype_in.parse()?))
+            .stype_out(export.stype_out.parse()?)
+            .mappings(mappings)
+            .build();
+
+        Ok(Self::new(
+            metadata,
+            build_publisher_venue_map(),
+            default_glbx_exchange_map(),
+        ))
+    }
+}
+
+impl<P: SymbolMapProvider> DatabentoSymbologyResolver<P> {
+    /// Create a resolver whose symbol maps are supplied by the given `provider` rather than
+    /// built from the `metadata`.
+    ///
+    /// The metadata is still used for the date range, dataset and symbology types of the
+    /// records being resolved.
+    #[must_use]
+    pub fn with_symbol_map_provider(
+        metadata: dbn::Metadata,
+        provider: P,
+        publisher_venue_map: IndexMap<PublisherId, Venue>,
+        glbx_exchange_map: HashMap<Symbol, Venue>,
+    ) -> Self {
+        Self::from_parts(
+            metadata,
+            Some(provider),
+            publisher_venue_map,
+            glbx_exchange_map,
+        )
+    }
+
+    fn from_parts(
+        metadata: dbn::Metadata,
+        symbol_map_provider: Option<P>,
+        publisher_venue_map: IndexMap<PublisherId, Venue>,
+        glbx_exchange_map: HashMap<Symbol, Venue>,
+    ) -> Self {
+        Self {
+            metadata,
+            symbol_map_provider,
+            publisher_venue_map: normalize_publisher_venue_map(publisher_venue_map),
+            glbx_exchange_map: normalize_glbx_exchange_map(glbx_exchange_map)
+                .into_iter()
+                .map(|(symbol, venue)| (symbol, GlbxVenueEntry::from(venue)))
+                .collect(),
+            venue_overrides: HashMap::new(),
+            symbol_maps: IndexMap::new(),
+            symbol_map_capacity: DEFAULT_SYMBOL_MAP_CAPACITY,
+            symbol_map_hits: 0,
+            interned_symbols: HashMap::new(),
+            interned_symbols_date: None,
+            symbol_interns: 0,
+            raw_symbols: HashMap::new(),
+            latest_symbols: None,
+            symbol_aliases: HashMap::new(),
+            share_class_separator: None,
+            ts_source: TimestampSource::default(),
+            daily_bar_offset: 0,
+            eod_session_close_offset: None,
+            pre_start_tolerance: None,
+            zero_ts_as_start: false,
+            prior_map_window: None,
+            date_tz: DateTimezone::default(),
+            skip_unsupported: false,
+            rtype_filter: Vec::new(),
+            strict_venues: false,
+            strict_publishers: false,
+            check_header_publisher: false,
+            instrument_ids: None,
+            instrument_ids_date: None,
+            venue_policy: VenueResolutionPolicy::default(),
+            fallback_venue: None,
+            metrics: None,
+            on_new_instrument: None,
+            distinct_instruments: HashSet::new(),
+            #[cfg(feature = "latency-histogram")]
+            latency: hdrhistogram::Histogram::new(3).expect("Invalid histogram precision"),
+        }
+    }
+
+    /// Return the metadata symbology is currently being resolved for.
+    #[must_use]
+    pub fn metadata(&self) -> &dbn::Metadata {
+        &self.metadata
+    }
+
+    /// Set the metadata to resolve symbology for, invalidating all cached symbol maps.
+    pub fn set_metadata(&mut self, metadata: dbn::Metadata) {
+        self.metadata = metadata;
+        self.symbol_maps.clear();
+        self.interned_symbols.clear();
+        self.interned_symbols_date = None;
+        if self.latest_symbols.is_some() {
+            self.latest_symbols = Some(latest_raw_symbols(&self.metadata));
+        }
+        self.clear_instrument_ids();
+    }
+
+    /// Reset the resolver to resolve symbology for the next file with the given `metadata`.
+    ///
+    /// Unlike `set_metadata` this also clears the inserted raw symbols, the symbol map hit
+    /// count and the distinct instruments resolved, as these are specific to a file. The venue
+    /// maps, symbol aliases and options are kept, with the cache allocations reused, so a single
+    /// resolver can process many files sequentially (such as the daily files of a backtest).
+    pub fn reset_metadata(&mut self, metadata: dbn::Metadata) {
+        self.set_metadata(metadata);
+        self.raw_symbols.clear();
+        self.symbol_map_hits = 0;
+        self.distinct_instruments.clear();
+    }
+
+    /// Return the number of distinct Nautilus `InstrumentId`s resolved since the last metadata
+    /// reset.
+    #[must_use]
+    pub fn distinct_instrument_count(&self) -> usize {
+        self.distinct_instruments.len()
+    }
+
+    /// Return the distinct Nautilus `InstrumentId`s resolved since the last metadata reset (in
+    /// arbitrary order).
+    pub fn distinct_instruments(&self) -> impl Iterator<Item = &InstrumentId> {
+        self.distinct_instruments.iter()
+    }
+
+    /// Return the number of resolutions which used an already cached symbol map.
+    #[must_use]
+    pub fn symbol_map_hits(&self) -> u64 {
+        self.symbol_map_hits
+    }
+
+    /// Return the number of raw symbols interned from the symbol maps, as each raw symbol is
+    /// interned once per date rather than on every resolution.
+    #[must_use]
+    pub fn symbol_interns(&self) -> u64 {
+        self.symbol_interns
+    }
+
+    /// Build and cache the symbol map for each date from `start` (inclusive) to `end`
+    /// (exclusive), so the first record of each date doesn't incur the build.
+    ///
+    /// All dates are attempted, and an error listing the dates whose symbol map could not be
+    /// built is returned if there are any. Only the last dates up to the symbol map capacity
+    /// remain cached.
+    pub fn prewarm(&mut self, start: time::Date, end: time::Date) -> Result<(), SymbologyError> {
+        let mut failed_dates = Vec::new();
+        let mut date = start;
+        while date < end {
+            if !self.symbol_maps.contains_key(&date) {
+                match self.build_symbol_map(date) {
+                    Ok(symbol_map) => self.insert_symbol_map(date, symbol_map),
+                    Err(_) => failed_dates.push(date),
+                }
+            }
+            match date.next_day() {
+                Some(next_date) => date = next_date,
+                None => break,
+            }
+        }
+
+        if failed_dates.is_empty() {
+            Ok(())
+        } else {
+            Err(SymbologyError::PrewarmFailed {
+                dates: failed_dates,
+            })
+        }
+    }
+
+    /// Set the maximum number of dates whose symbol maps are cached (at least one), evicting
+    /// the least recently used symbol maps beyond it.
+    ///
+    /// This bounds the memory of long-running live sessions, as an evicted date is rebuilt
+    /// from the metadata if it is resolved again. The default capacity is 8 dates.
+    pub fn set_symbol_map_capacity(&mut self, capacity: usize) {
+        self.symbol_map_capacity = capacity.max(1);
+        while self.symbol_maps.len() > self.symbol_map_capacity {
+            self.symbol_maps.shift_remove_index(0);
+        }
+    }
+
+    /// Set the record timestamp used to select the date for symbol map resolution.
+    pub fn set_timestamp_source(&mut self, ts_source: TimestampSource) {
+        self.ts_source = ts_source;
+    }
+
+    /// Set the timezone in which the date for symbol map resolution is selected from the record
+    /// timestamp, which defaults to UTC.
+    pub fn set_date_timezone(&mut self, date_tz: DateTimezone) {
+        self.date_tz = date_tz;
+    }
+
+    /// Set the offset (nanoseconds) subtracted from the `ts_event` of daily and EOD OHLCV bars
+    /// before selecting the symbol map date.
+    ///
+    /// Daily bars are timestamped at the session close, which for some venues is after UTC
+    /// midnight of the bar's trading date, so the offset maps the close back to that date.
+    pub fn set_daily_bar_offset(&mut self, offset_ns: u64) {
+        self.daily_bar_offset = offset_ns;
+    }
+
+    /// Set the session close offset (nanoseconds) of EOD OHLCV bars, which is subtracted from
+    /// their `ts_event` before selecting the symbol map date instead of the daily bar offset.
+    ///
+    /// An EOD bar is emitted after its session has closed, so its `ts_event` may fall on the
+    /// following calendar day. The offset is the time of day (UTC) of the session close, such
+    /// that any EOD bar emitted before the next session's close resolves to its trading date.
+    pub fn set_eod_session_close_offset(&mut self, offset_ns: u64) {
+        self.eod_session_close_offset = Some(offset_ns);
+    }
+
+    /// Set the tolerance (nanoseconds) for records timestamped before the metadata `start`,
+    /// which are resolved against the symbol map of the start date with a warning.
+    ///
+    /// This rescues records whose clocks are skewed by a few seconds at the session open.
+    /// Records further before `start` than the tolerance resolve to an error, replacing the
+    /// default clamping of records up to one day before the start date.
+    pub fn set_pre_start_tolerance(&mut self, tolerance_ns: Option<u64>) {
+        self.pre_start_tolerance = tolerance_ns;
+    }
+
+    /// Set whether records with a zero timestamp (as left by some synthetic data generators)
+    /// resolve against the symbol map of the metadata `start` date, rather than 1970-01-01.
+    ///
+    /// This is separate from the handling of the `UNDEF_TIMESTAMP` sentinel, and is off by
+    /// default so that zero timestamps in real data are not silently resolved.
+    pub fn set_zero_ts_as_start(&mut self, zero_ts_as_start: bool) {
+        self.zero_ts_as_start = zero_ts_as_start;
+    }
+
+    /// Set the number of days to fall back through for the nearest prior non-empty symbol map,
+    /// when the symbol map for a record's date is empty (e.g. a holiday gap in the mappings).
+    ///
+    /// Note this can resolve stale symbols, as a raw symbol renamed on the gap date will
+    /// resolve to its name on the prior date. The cached symbol maps (and the symbol map hit
+    /// count) are cleared, as they were built with the previous window.
+    pub fn set_prior_map_window(&mut self, window_days: Option<u32>) {
+        self.prior_map_window = window_days;
+        self.symbol_maps.clear();
+        self.symbol_map_hits = 0;
+        self.interned_symbols.clear();
+        self.interned_symbols_date = None;
+        self.clear_instrument_ids();
+    }
+
+    /// Set whether records with an unsupported record type resolve to `Ok(None)` from
+    /// `try_resolve` (so they can be filtered out), rather than an error.
+    pub fn set_skip_unsupported(&mut self, skip_unsupported: bool) {
+        self.skip_unsupported = skip_unsupported;
+    }
+
+    /// Set the record types resolved by `try_resolve`, with all other records resolving to
+    /// `Ok(None)` before any symbol map lookup. An empty filter resolves all record types.
+    pub fn set_rtype_filter(&mut self, rtypes: &[dbn::RType]) {
+        self.rtype_filter = rtypes.iter().map(|rtype| *rtype as u8).collect();
+    }
+
+    /// Set whether resolved venues are validated against the known ISO 10383 market
+    /// identifier codes (MICs), with unknown venues resolving to an error.
+    pub fn set_strict_venues(&mut self, strict_venues: bool) {
+        self.strict_venues = strict_venues;
+        self.clear_instrument_ids();
+    }
+
+    /// Set whether the `publisher_id` of each record is validated as a publisher of the
+    /// metadata dataset before resolving, as a foreign publisher generally indicates a decoding
+    /// or routing bug. This is opt-in, as the check is made on every cache miss.
+    pub fn set_strict_publishers(&mut self, strict_publishers: bool) {
+        self.strict_publishers = strict_publishers;
+        self.clear_instrument_ids();
+    }
+
+    /// Set whether the `publisher_id` passed to `resolve` (and `resolve_record`) is checked
+    /// against the `publisher_id` of the record header, with a mismatch resolving to an error
+    /// rather than a venue for the wrong publisher.
+    pub fn set_check_header_publisher(&mut self, check_header_publisher: bool) {
+        self.check_header_publisher = check_header_publisher;
+    }
+
+    /// Insert the raw symbol for the given Databento `instrument_id`.
+    ///
+    /// When the metadata was requested with continuous or parent symbology, its symbol map
+    /// only holds the requested symbols (e.g. `ES.c.0`), so the actual contract raw symbols
+    /// need to be provided (typically from definition records).
+    pub fn insert_raw_symbol(&mut self, instrument_id: u32, raw_symbol: Ustr) {
+        self.raw_symbols.insert(instrument_id, raw_symbol);
+        self.clear_instrument_ids();
+    }
+
+    /// Set whether instrument IDs use the latest raw symbol of each Databento `instrument_id`
+    /// within the metadata, rather than the raw symbol on the record's date.
+    ///
+    /// Databento occasionally renames a raw symbol while keeping its numeric `instrument_id`,
+    /// in which case records before the rename would otherwise resolve to the outdated symbol.
+    /// Enabling this keeps a single instrument ID per `instrument_id` across the file (display
+    /// consistency), at the cost of historical accuracy, as earlier records are identified by a
+    /// symbol which did not exist yet on their date. The symbol map for the record's date is
+    /// still required to contain the `instrument_id`.
+    pub fn set_latest_symbol_names(&mut self, enabled: bool) {
+        self.latest_symbols = enabled.then(|| latest_raw_symbols(&self.metadata));
+        self.clear_instrument_ids();
+    }
+
+    /// Set the precedence of the venue maps when resolving venues.
+    pub fn set_venue_policy(&mut self, venue_policy: VenueResolutionPolicy) {
+        self.venue_policy = venue_policy;
+        self.clear_instrument_ids();
+    }
+
+    /// Set the venue used when neither the GLBX exchange map nor the publisher venue map has a
+    /// venue for a record, rather than resolving to an `UnknownPublisher` error.
+    ///
+    /// This is intended for exploratory loads, where a placeholder venue (e.g. `UNDEFINED`)
+    /// allows inspecting which records are missing a venue mapping.
+    pub fn set_fallback_venue(&mut self, fallback_venue: Option<Venue>) {
+        self.fallback_venue = fallback_venue;
+        self.clear_instrument_ids();
+    }
+
+    /// Set the aliases which rewrite Databento raw symbols to canonical symbols (e.g. `LCO` to
+    /// `BRN`) before the venue is resolved, so the GLBX exchange map is keyed by canonical symbol.
+    ///
+    /// Raw symbols without an alias are used as is.
+    pub fn set_symbol_aliases(&mut self, symbol_aliases: HashMap<Ustr, Ustr>) {
+        self.symbol_aliases = symbol_aliases;
+        self.clear_instrument_ids();
+    }
+
+    /// Set the canonical share class separator which the raw symbols of US equity publishers
+    /// are normalized to (e.g. `BRK/B` to `BRK.B`), before any alias is applied.
+    ///
+    /// Raw symbols of other publishers (such as GLBX and OPRA) are not normalized, and no
+    /// normalization is applied by default.
+    pub fn set_share_class_separator(&mut self, separator: Option<ShareClassSeparator>) {
+        self.share_class_separator = separator;
+        self.clear_instrument_ids();
+    }
+
+    /// Set the venue overrides for specific `(publisher_id, symbol)` pairs (such as dually
+    /// listed products), which are consulted before the GLBX exchange and publisher venue maps.
+    ///
+    /// The overrides are keyed by the symbol after any alias is applied, and their symbols and
+    /// venues are normalized as for the venue maps.
+    pub fn set_venue_overrides(&mut self, venue_overrides: HashMap<(PublisherId, Symbol), Venue>) {
+        self.venue_overrides = venue_overrides
+            .into_iter()
+            .map(|((publisher_id, symbol), venue)| {
+                (
+                    (publisher_id, normalize_symbol(symbol)),
+                    normalize_venue(venue),
+                )
+            })
+            .collect();
+        self.clear_instrument_ids();
+    }
+
+    /// Set the GLBX exchange map entries for symbols whose venue depends on the publisher,
+    /// replacing any existing entries for the same symbols.
+    ///
+    /// A symbol resolved from the GLBX exchange map uses the venue for its `publisher_id` if
+    /// the entry has one, otherwise the default venue of the entry.
+    pub fn set_glbx_venue_entries(&mut self, entries: HashMap<Symbol, GlbxVenueEntry>) {
+        self.glbx_exchange_map.extend(
+            entries
+                .into_iter()
+                .map(|(symbol, entry)| (normalize_symbol(symbol), entry.normalized())),
+        );
+        self.clear_instrument_ids();
+    }
+
+    /// Set whether resolved instrument IDs are cached by Databento `instrument_id` and
+    /// `publisher_id`, so that subsequent records for an instrument skip the symbol lookup.
+    ///
+    /// The cache only holds resolutions for a single date, and is cleared when the date of
+    /// the resolved records changes (as continuous symbology can remap between dates).
+    pub fn set_instrument_id_cache(&mut self, enabled: bool) {
+        self.instrument_ids = enabled.then(IndexMap::new);
+        self.instrument_ids_date = None;
+    }
+
+    /// Set whether resolutions from `resolve` (and the methods calling it) are counted.
+    pub fn set_metrics_enabled(&mut self, enabled: bool) {
+        self.metrics = enabled.then(ResolverMetrics::default);
+    }
+
+    /// Set the hook called the first time each distinct Nautilus `InstrumentId` is resolved by
+    /// `resolve` (and the methods calling it) or `resolve_record`, such as for registering
+    /// instruments with the cache on first sight in a stream.
+    ///
+    /// The hook is called before the instrument ID is returned. Instruments resolved before the
+    /// hook was set are not notified, and the hook is called again for an instrument resolved
+    /// after a `reset_metadata`.
+    pub fn set_on_new_instrument(
+        &mut self,
+        on_new_instrument: impl FnMut(&InstrumentId) + Send + 'static,
+    ) {
+        self.on_new_instrument = Some(Box::new(on_new_instrument));
+    }
+
+    /// Return the resolution metrics, if enabled.
+    #[must_use]
+    pub fn metrics(&self) -> Option<&ResolverMetrics> {
+        self.metrics.as_ref()
+    }
+
+    /// Return a copy of the histogram of `resolve` latencies (nanoseconds).
+    #[cfg(feature = "latency-histogram")]
+    #[must_use]
+    pub fn latency_snapshot(&self) -> hdrhistogram::Histogram<u64> {
+        self.latency.clone()
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given record.
+    #[tracing::instrument(
+        level = "debug",
+        skip(self, rec_ref),
+        fields(rtype = rec_ref.header().rtype, date = tracing::field::Empty)
+    )]
+    pub fn resolve(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+        publisher_id: PublisherId,
+    ) -> Result<InstrumentId, SymbologyError> {
+        #[cfg(feature = "latency-histogram")]
+        let started = std::time::Instant::now();
+        let result = self
+            .check_publisher(rec_ref.header(), publisher_id)
+            .and_then(|()| self.decode_instrument_id_and_date(rec_ref))
+            .and_then(|(instrument_id, date)| {
+                self.resolve_cached(instrument_id, date, publisher_id)
+            });
+        self.record_metrics(&result, publisher_id, rec_ref.header().rtype);
+        self.track_instrument(&result);
+        #[cfg(feature = "latency-histogram")]
+        self.latency
+            .saturating_record(u64::try_from(started.elapsed().as_nanos()).unwrap_or(u64::MAX));
+        result
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given record, as for `resolve` with the
+    /// `publisher_id` of the record header.
+    pub fn resolve_from_header(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+    ) -> Result<InstrumentId, SymbologyError> {
+        self.resolve(rec_ref, rec_ref.header().publisher_id)
+    }
+
+    /// Resolve the given record, classifying error and system records as control messages
+    /// rather than failing with an unsupported record type, so live clients can react to
+    /// heartbeats and gateway errors without aborting the session.
+    pub fn resolve_outcome(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+        publisher_id: PublisherId,
+    ) -> Result<ResolveOutcome, SymbologyError> {
+        if let Some(control) = ControlMsg::from_record(rec_ref) {
+            return Ok(ResolveOutcome::Control(control));
+        }
+        self.resolve(rec_ref, publisher_id)
+            .map(ResolveOutcome::Instrument)
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given owned record (such as a `TradeMsg` or
+    /// `OhlcvMsg`), as for `resolve` but without needing a `RecordRef`.
+    pub fn resolve_record<R: SymbologyRecord>(
+        &mut self,
+        record: &R,
+        publisher_id: PublisherId,
+    ) -> Result<InstrumentId, SymbologyError> {
+        let header = record.header();
+        let ts = select_ts(
+            header,
+            record.ts_recv(),
+            self.ts_source,
+            self.daily_bar_offset,
+            self.eod_session_close_offset
+                .unwrap_or(self.daily_bar_offset),
+            self.metadata.start,
+        );
+        let result = self
+            .check_publisher(header, publisher_id)
+            .and_then(|()| self.date_for_ts(ts, header.rtype))
+            .and_then(|date| self.resolve_cached(header.instrument_id, date, publisher_id));
+        self.record_metrics(&result, publisher_id, header.rtype);
+        self.track_instrument(&result);
+        result
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given MBP-10 record, also returning the
+    /// decoded `Mbp10Msg` (borrowed from the record) so callers can read the book levels
+    /// without decoding the record again.
+    pub fn resolve_mbp10<'a>(
+        &mut self,
+        rec_ref: &'a dbn::RecordRef<'_>,
+        publisher_id: PublisherId,
+    ) -> Result<(InstrumentId, &'a dbn::Mbp10Msg), SymbologyError> {
+        let Some(msg) = rec_ref.get::<dbn::Mbp10Msg>() else {
+            let rtype = rec_ref.header().rtype;
+            let error = SymbologyError::UnsupportedRType { rtype };
+            if let Some(metrics) = self.metrics.as_mut() {
+                metrics.record_error(&error);
+            }
+            return Err(error);
+        };
+        let instrument_id = self.resolve_record(msg, publisher_id)?;
+        Ok((instrument_id, msg))
+    }
+
+    fn check_publisher(
+        &self,
+        header: &dbn::RecordHeader,
+        publisher_id: PublisherId,
+    ) -> Result<(), SymbologyError> {
+        if self.check_header_publisher && header.publisher_id != publisher_id {
+            return Err(SymbologyError::PublisherMismatch {
+                publisher_id,
+                header_publisher_id: header.publisher_id,
+            });
+        }
+        Ok(())
+    }
+
+    fn track_instrument(&mut self, result: &Result<InstrumentId, SymbologyError>) {
+        let Ok(instrument_id) = result else {
+            return;
+        };
+        if self.distinct_instruments.insert(*instrument_id) {
+            if let Some(on_new_instrument) = self.on_new_instrument.as_mut() {
+                on_new_instrument(instrument_id);
+            }
+        }
+    }
+
+    fn record_metrics(
+        &mut self,
+        result: &Result<InstrumentId, SymbologyError>,
+        publisher_id: PublisherId,
+        rtype: u8,
+    ) {
+        if let Some(metrics) = self.metrics.as_mut() {
+            match result {
+                Ok(_) => metrics.record_resolved(publisher_id, rtype),
+                Err(e) => metrics.record_error(e),
+            }
+        }
+    }
+
+    fn resolve_cached(
+        &mut self,
+        instrument_id: u32,
+        date: time::Date,
+        publisher_id: PublisherId,
+    ) -> Result<InstrumentId, SymbologyError> {
+        let Some(instrument_ids) = self.instrument_ids.as_mut() else {
+            return self
+                .resolve_full_for_date(instrument_id, date, publisher_id)
+                .map(|resolved| resolved.instrument_id);
+        };
+
+        if self.instrument_ids_date != Some(date) {
+            instrument_ids.clear();
+            self.instrument_ids_date = Some(date);
+        } else if let Some(cached) = instrument_ids.get(&(instrument_id, publisher_id)) {
+            return Ok(*cached);
+        }
+
+        let resolved = self
+            .resolve_full_for_date(instrument_id, date, publisher_id)?
+            .instrument_id;
+        if let Some(instrument_ids) = self.instrument_ids.as_mut() {
+            instrument_ids.insert((instrument_id, publisher_id), resolved);
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given record into `out`, overwriting it in
+    /// place rather than returning a new instrument ID.
+    ///
+    /// The contents of `out` are only valid if `Ok(())` is returned.
+    pub fn resolve_into(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+        publisher_id: PublisherId,
+        out: &mut InstrumentId,
+    ) -> Result<(), SymbologyError> {
+        *out = self.resolve(rec_ref, publisher_id)?;
+        Ok(())
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given record, returning `None` for records
+    /// with an unsupported record type if the resolver is set to skip them, or for records
+    /// excluded by the record type filter.
+    pub fn try_resolve(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+        publisher_id: PublisherId,
+    ) -> Result<Option<InstrumentId>, SymbologyError> {
+        if !self.rtype_filter.is_empty() && !self.rtype_filter.contains(&rec_ref.header().rtype) {
+            return Ok(None);
+        }
+
+        match self.resolve(rec_ref, publisher_id) {
+            Ok(instrument_id) => Ok(Some(instrument_id)),
+            Err(SymbologyError::UnsupportedRType { .. }) if self.skip_unsupported => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given record, rendered in the given format
+    /// (see `format_instrument_id`).
+    pub fn resolve_formatted(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+        publisher_id: PublisherId,
+        fmt: IdFormat,
+    ) -> Result<String, SymbologyError> {
+        self.resolve(rec_ref, publisher_id)
+            .map(|instrument_id| format_instrument_id(&instrument_id, fmt))
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for each of the given records, in input order.
+    ///
+    /// Unlike `decode_nautilus_instrument_ids` this never aborts, returning a result for every
+    /// record (errors included) so that failures can be audited. The `publisher_id` of each
+    /// record is taken from its header.
+    pub fn resolve_all(
+        &mut self,
+        records: &[dbn::RecordRef],
+    ) -> Vec<Result<InstrumentId, SymbologyError>> {
+        records
+            .iter()
+            .map(|rec_ref| self.resolve(rec_ref, rec_ref.header().publisher_id))
+            .collect()
+    }
+
+    /// Resolve the Nautilus `InstrumentId` of each of the given records, ordered by Databento
+    /// `instrument_id` with ties broken by symbol (then venue), for deterministic output such
+    /// as snapshot tests. Records of the same instrument each yield an `InstrumentId`.
+    ///
+    /// The `publisher_id` of each record is taken from its header, and the first record which
+    /// can't be resolved aborts with an error for its index.
+    pub fn resolve_sorted(
+        &mut self,
+        records: &[dbn::RecordRef],
+    ) -> Result<Vec<InstrumentId>, SymbologyError> {
+        let mut resolved = Vec::with_capacity(records.len());
+        for (index, rec_ref) in records.iter().enumerate() {
+            let header = rec_ref.header();
+            let instrument_id =
+                self.resolve(rec_ref, header.publisher_id)
+                    .map_err(|e| SymbologyError::Record {
+                        index,
+                        source: Box::new(e),
+                    })?;
+            resolved.push((header.instrument_id, instrument_id));
+        }
+
+        resolved.sort_by(|(a_id, a), (b_id, b)| {
+            a_id.cmp(b_id)
+                .then_with(|| a.symbol.value.as_str().cmp(b.symbol.value.as_str()))
+                .then_with(|| a.venue.value.as_str().cmp(b.venue.value.as_str()))
+        });
+        Ok(resolved
+            .into_iter()
+            .map(|(_, instrument_id)| instrument_id)
+            .collect())
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for each of the given records in a single pass,
+    /// grouped by venue with the index of each record, so venue shards can be reassembled in
+    /// input order.
+    ///
+    /// The `publisher_id` of each record is taken from its header. Records which can't be
+    /// resolved don't abort the pass, and are returned as errors for their index.
+    pub fn resolve_grouped(
+        &mut self,
+        records: &[dbn::RecordRef],
+    ) -> (
+        HashMap<Venue, Vec<(InstrumentId, usize)>>,
+        Vec<SymbologyError>,
+    ) {
+        let mut grouped: HashMap<Venue, Vec<(InstrumentId, usize)>> = HashMap::new();
+        let mut errors = Vec::new();
+        for (index, rec_ref) in records.iter().enumerate() {
+            match self.resolve(rec_ref, rec_ref.header().publisher_id) {
+                Ok(instrument_id) => grouped
+                    .entry(instrument_id.venue)
+                    .or_default()
+                    .push((instrument_id, index)),
+                Err(e) => errors.push(SymbologyError::Record {
+                    index,
+                    source: Box::new(e),
+                }),
+            }
+        }
+        (grouped, errors)
+    }
+
+    /// Resolve the full Databento symbology for the given record.
+    ///
+    /// For continuous and parent symbology the mapping is honored per date, so a continuous
+    /// symbol resolves to the contract it referenced on the record's date. If no raw symbol
+    /// was inserted for the resolved contract then the continuous symbol itself is used.
+    ///
+    /// The asset class and instrument class are only resolved for instrument definitions.
+    #[tracing::instrument(
+        level = "debug",
+        skip(self, rec_ref),
+        fields(rtype = rec_ref.header().rtype, date = tracing::field::Empty)
+    )]
+    pub fn resolve_full(
+        &mut self,
+        rec_ref: &dbn::RecordRef,
+        publisher_id: PublisherId,
+    ) -> Result<ResolvedInstrument, SymbologyError> {
+        let (instrument_id, date) = self.decode_instrument_id_and_date(rec_ref)?;
+        let mut resolved = self.resolve_full_for_date(instrument_id, date, publisher_id)?;
+        (resolved.asset_class, resolved.instrument_class) =
+            definition_classes(rec_ref, resolved.dataset);
+        Ok(resolved)
+    }
+
+    fn decode_instrument_id_and_date(
+        &self,
+        rec_ref: &dbn::RecordRef,
+    ) -> Result<(u32, time::Date), SymbologyError> {
+        let (instrument_id, nanoseconds) = decode_instrument_id_and_ts(
+            rec_ref,
+            self.ts_source,
+            self.daily_bar_offset,
+            self.eod_session_close_offset
+                .unwrap_or(self.daily_bar_offset),
+            self.metadata.start,
+        )?;
+        let date = self.date_for_ts(nanoseconds, rec_ref.header().rtype)?;
+        Ok((instrument_id, date))
+    }
+
+    fn date_for_ts(&self, nanoseconds: u64, rtype: u8) -> Result<time::Date, SymbologyError> {
+        let nanoseconds = if nanoseconds == 0 && self.zero_ts_as_start {
+            self.metadata.start
+        } else {
+            nanoseconds
+        };
+        let date = match self.pre_start_tolerance {
+            Some(tolerance) if nanoseconds < self.metadata.start => {
+                let skew = self.metadata.start - nanoseconds;
+                if skew > tolerance {
+                    return Err(SymbologyError::BeforeStart {
+                        ts: nanoseconds,
+                        start: self.metadata.start,
+                        tolerance,
+                    });
+                }
+                let start_date = self.metadata.start().date();
+                tracing::warn!(
+                    "Timestamp {nanoseconds} precedes metadata start by {skew}ns, resolving for {start_date}"
+                );
+                start_date
+            }
+            _ => clamp_to_metadata_dates(
+                ts_to_date(nanoseconds, rtype, self.date_tz)?,
+                &self.metadata,
+            ),
+        };
+        tracing::Span::current().record("date", tracing::field::display(date));
+        Ok(date)
+    }
+
+    fn resolve_full_for_date(
+        &mut self,
+        instrument_id: u32,
+        date: time::Date,
+        publisher_id: PublisherId,
+    ) -> Result<ResolvedInstrument, SymbologyError> {
+        if self.strict_publishers
+            && !is_dataset_publisher(publisher_id, &[self.metadata.dataset.as_str()])
+        {
+            return Err(SymbologyError::ForeignPublisher {
+                publisher_id,
+                dataset: self.metadata.dataset.clone(),
+            });
+        }
+
+        let mapped_symbol = self.mapped_symbol(instrument_id, publisher_id, date)?;
+
+        let (raw_symbol, continuous_symbol) = match self.metadata.stype_in {
+            Some(dbn::SType::Continuous | dbn::SType::Parent) => {
+                let raw_symbol = self
+                    .raw_symbols
+                    .get(&instrument_id)
+                    .copied()
+                    .unwrap_or(mapped_symbol);
+                (raw_symbol, Some(mapped_symbol))
+            }
+            _ => {
+                let raw_symbol = self
+                    .latest_symbols
+                    .as_ref()
+                    .and_then(|latest_symbols| latest_symbols.get(&instrument_id))
+                    .copied()
+                    .unwrap_or(mapped_symbol);
+                (raw_symbol, None)
+            }
+        };
+
+        let instrument_id =
+            self.resolve_raw_symbol(raw_symbol, Some(instrument_id), publisher_id)?;
+
+        let trimmed_symbol = raw_symbol.trim_end_matches('\0');
+        let leg_count = spread_leg_count(trimmed_symbol);
+        Ok(ResolvedInstrument {
+            instrument_id,
+            raw_symbol: if trimmed_symbol.len() == raw_symbol.len() {
+                raw_symbol
+            } else {
+                Ustr::from(trimmed_symbol)
+            },
+            publisher_id,
+            dataset: dataset_for_publisher(publisher_id, &self.metadata).map(Ustr::from),
+            continuous_symbol,
+            is_spread: leg_count > 1,
+            leg_count,
+            asset_class: None,
+            instrument_class: None,
+        })
+    }
+
+    /// Return the interned raw symbol mapped to the Databento `instrument_id` on `date`.
+    ///
+    /// The interned symbols of the last resolved date are cached by `instrument_id`, so hot
+    /// instruments skip the hashing (and global lock) of interning on every resolution.
+    fn mapped_symbol(
+        &mut self,
+        instrument_id: u32,
+        publisher_id: PublisherId,
+        date: time::Date,
+    ) -> Result<Ustr, SymbologyError> {
+        self.symbol_map_for_date(date)?;
+        if self.interned_symbols_date != Some(date) {
+            self.interned_symbols.clear();
+            self.interned_symbols_date = Some(date);
+        }
+        if let Some(symbol) = self.interned_symbols.get(&instrument_id) {
+            return Ok(*symbol);
+        }
+
+        let symbol = Ustr::from(raw_symbol_for_date(
+            &self.symbol_maps[&date],
+            instrument_id,
+            publisher_id,
+            date,
+        )?);
+        self.symbol_interns += 1;
+        self.interned_symbols.insert(instrument_id, symbol);
+        Ok(symbol)
+    }
+
+    /// Resolve the Nautilus `InstrumentId` for the given `raw_symbol` with the venue maps,
+    /// symbol aliases and venue options of the resolver.
+    pub(super) fn resolve_raw_symbol(
+        &self,
+        raw_symbol: Ustr,
+        instrument_id: Option<u32>,
+        publisher_id: PublisherId,
+    ) -> Result<InstrumentId, SymbologyError> {
+        let raw_symbol = match self.share_class_separator {
+            Some(separator)
+                if is_equity_publisher(publisher_id)
+                    && raw_symbol.contains(SHARE_CLASS_SEPARATORS) =>
+            {
+                Ustr::from(&separator.normalize(raw_symbol.as_str()))
+            }
+            _ => raw_symbol,
+        };
+        let venue_override = |symbol: &Symbol, publisher_id: PublisherId| {
+            self.venue_overrides.get(&(publisher_id, *symbol)).copied()
+        };
+        let instrument_id = resolve_instrument_id(
+            raw_symbol,
+            instrument_id,
+            publisher_id,
+            &self.publisher_venue_map,
+            &self.glbx_exchange_map,
+            &ResolveOptions {
+                symbol_aliases: Some(&self.symbol_aliases),
+                venue_policy: self.venue_policy,
+                fallback_venue: self.fallback_venue,
+                venue_resolver: Some(&venue_override),
+            },
+        )?;
+
+        if self.strict_venues && !instrument_id.venue.is_mic() {
+            return Err(SymbologyError::InvalidVenue {
+                venue: instrument_id.venue,
+                instrument_id,
+            });
+        }
+        Ok(instrument_id)
+    }
+
+    fn clear_instrument_ids(&mut self) {
+        if let Some(instrument_ids) = self.instrument_ids.as_mut() {
+            instrument_ids.clear();
+        }
+        self.instrument_ids_date = None;
+    }
+
+    fn symbol_map_for_date(&mut self, date: time::Date) -> Result<&PitSymbolMap, SymbologyError> {
+        if let Some(index) = self.symbol_maps.get_index_of(&date) {
+            self.symbol_map_hits += 1;
+            // Most recently used symbol maps are kept at the end
+            let last = self.symbol_maps.len() - 1;
+            self.symbol_maps.move_index(index, last);
+        } else {
+            tracing::debug!("Building symbol map for {date}, not cached");
+            let symbol_map = self.build_symbol_map(date)?;
+            self.insert_symbol_map(date, symbol_map);
+        }
+        Ok(&self.symbol_maps[&date])
+    }
+
+    fn build_symbol_map(&self, date: time::Date) -> Result<PitSymbolMap, SymbologyError> {
+        let symbol_map = self.build_symbol_map_for_date(date)?;
+        let Some(window_days) = self.prior_map_window else {
+            return Ok(symbol_map);
+        };
+        if !symbol_map.inner().is_empty() {
+            return Ok(symbol_map);
+        }
+
+        let mut prior_date = date;
+        for _ in 0..window_days {
+            let Some(previous_day) = prior_date.previous_day() else {
+                break;
+            };
+            prior_date = previous_day;
+            // Dates outside the metadata range have no symbol map to fall back to
+            let Ok(prior_map) = self.build_symbol_map_for_date(prior_date) else {
+                break;
+            };
+            if !prior_map.inner().is_empty() {
+                tracing::warn!("Empty symbol map for {date}, using symbol map for {prior_date}");
+                return Ok(prior_map);
+            }
+        }
+        Ok(symbol_map)
+    }
+
+    fn build_symbol_map_for_date(&self, date: time::Date) -> Result<PitSymbolMap, SymbologyError> {
+        match &self.symbol_map_provider {
+            Some(provider) => provider.symbol_map_for_date(date),
+            None => build_symbol_map(&self.metadata, date),
+        }
+    }
+
+    fn insert_symbol_map(&mut self, date: time::Date, symbol_map: PitSymbolMap) {
+        self.symbol_maps.insert(date, symbol_map);
+        while self.symbol_maps.len() > self.symbol_map_capacity {
+            let (evicted_date, _) = self
+                .symbol_maps
+                .shift_remove_index(0)
+                .expect("Symbol maps should not be empty");
+            tracing::debug!("Evicted symbol map for {evicted_date}");
+        }
+    }
+}
+
+/// Represents a venue map entry whose venue is not a known ISO 10383 market identifier code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidVenueEntry {
+    /// An entry of the publisher venue map.
+    Publisher {
+        publisher_id: PublisherId,
+        venue: Venue,
+    },
+    /// An entry of the GLBX exchange map.
+    GlbxExchange { symbol: Symbol, venue: Venue },
+}
+
+/// Provides a fluent builder for a `DatabentoSymbologyResolver`.
+///
+/// The venue maps default to empty, and all other options to those of
+/// `DatabentoSymbologyResolver::new`.
+#[derive(Debug)]
+pub struct DatabentoSymbologyResolverBuilder {
+    metadata: dbn::Metadata,
+    publisher_venue_map: IndexMap<PublisherId, Venue>,
+    glbx_exchange_map: HashMap<Symbol, Venue>,
+    glbx_venue_entries: HashMap<Symbol, GlbxVenueEntry>,
+    venue_overrides: HashMap<(PublisherId, Symbol), Venue>,
+    symbol_aliases: HashMap<Ustr, Ustr>,
+    share_class_separator: Option<ShareClassSeparator>,
+    latest_symbol_names: bool,
+    ts_source: TimestampSource,
+    date_tz: DateTimezone,
+    daily_bar_offset: u64,
+    eod_session_close_offset: Option<u64>,
+    pre_start_tolerance: Option<u64>,
+    zero_ts_as_start: bool,
+    prior_map_window: Option<u32>,
+    venue_policy: VenueResolutionPolicy,
+    fallback_venue: Option<Venue>,
+    strict_venues: bool,
+    strict_publishers: bool,
+    check_header_publisher: bool,
+    skip_unsupported: bool,
+    rtype_filter: Vec<dbn::RType>,
+    instrument_id_cache: bool,
+    symbol_map_capacity: usize,
+    metrics_enabled: bool,
+    on_new_instrument: Option<Box<dyn FnMut(&InstrumentId) + Send>>,
+}
+
+impl DatabentoSymbologyResolverBuilder {
+    #[must_use]
+    pub fn new(metadata: dbn::Metadata) -> Self {
+        Self {
+            metadata,
+            publisher_venue_map: IndexMap::new(),
+            glbx_exchange_map: HashMap::new(),
+            glbx_venue_entries: HashMap::new(),
+            venue_overrides: HashMap::new(),
+            symbol_aliases: HashMap::new(),
+            share_class_separator: None,
+            latest_symbol_names: false,
+            ts_source: TimestampSource::default(),
+            date_tz: DateTimezone::default(),
+            daily_bar_offset: 0,
+            eod_session_close_offset: None,
+            pre_start_tolerance: None,
+            zero_ts_as_start: false,
+            prior_map_window: None,
+            venue_policy: VenueResolutionPolicy::default(),
+            fallback_venue: None,
+            strict_venues: false,
+            strict_publishers: false,
+            check_header_publisher: false,
+            skip_unsupported: false,
+            rtype_filter: Vec::new(),
+            instrument_id_cache: false,
+            symbol_map_capacity: DEFAULT_SYMBOL_MAP_CAPACITY,
+            metrics_enabled: false,
+            on_new_instrument: None,
+        }
+    }
+
+    #[must_use]
+    pub fn publisher_venue_map(
+        mut self,
+        publisher_venue_map: IndexMap<PublisherId, Venue>,
+    ) -> Self {
+        self.publisher_venue_map = publisher_venue_map;
+        self
+    }
+
+    #[must_use]
+    pub fn glbx_exchange_map(mut self, glbx_exchange_map: HashMap<Symbol, Venue>) -> Self {
+        self.glbx_exchange_map = glbx_exchange_map;
+        self
+    }
+
+    #[must_use]
+    pub fn glbx_venue_entries(mut self, entries: HashMap<Symbol, GlbxVenueEntry>) -> Self {
+        self.glbx_venue_entries = entries;
+        self
+    }
+
+    #[must_use]
+    pub fn venue_overrides(
+        mut self,
+        venue_overrides: HashMap<(PublisherId, Symbol), Venue>,
+    ) -> Self {
+        self.venue_overrides = venue_overrides;
+        self
+    }
+
+    #[must_use]
+    pub fn symbol_aliases(mut self, symbol_aliases: HashMap<Ustr, Ustr>) -> Self {
+        self.symbol_aliases = symbol_aliases;
+        self
+    }
+
+    #[must_use]
+    pub fn share_class_separator(mut self, separator: Option<ShareClassSeparator>) -> Self {
+        self.share_class_separator = separator;
+        self
+    }
+
+    #[must_use]
+    pub fn latest_symbol_names(mut self, enabled: bool) -> Self {
+        self.latest_symbol_names = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn timestamp_source(mut self, ts_source: TimestampSource) -> Self {
+        self.ts_source = ts_source;
+        self
+    }
+
+    #[must_use]
+    pub fn date_timezone(mut self, date_tz: DateTimezone) -> Self {
+        self.date_tz = date_tz;
+        self
+    }
+
+    #[must_use]
+    pub fn daily_bar_offset(mut self, offset_ns: u64) -> Self {
+        self.daily_bar_offset = offset_ns;
+        self
+    }
+
+    #[must_use]
+    pub fn eod_session_close_offset(mut self, offset_ns: u64) -> Self {
+        self.eod_session_close_offset = Some(offset_ns);
+        self
+    }
+
+    #[must_use]
+    pub fn pre_start_tolerance(mut self, tolerance_ns: Option<u64>) -> Self {
+        self.pre_start_tolerance = tolerance_ns;
+        self
+    }
+
+    #[must_use]
+    pub fn zero_ts_as_start(mut self, zero_ts_as_start: bool) -> Self {
+        self.zero_ts_as_start = zero_ts_as_start;
+        self
+    }
+
+    #[must_use]
+    pub fn prior_map_window(mut self, window_days: Option<u32>) -> Self {
+        self.prior_map_window = window_days;
+        self
+    }
+
+    #[must_use]
+    pub fn venue_policy(mut self, venue_policy: VenueResolutionPolicy) -> Self {
+        self.venue_policy = venue_policy;
+        self
+    }
+
+    #[must_use]
+    pub fn fallback_venue(mut self, fallback_venue: Option<Venue>) -> Self {
+        self.fallback_venue = fallback_venue;
+        self
+    }
+
+    #[must_use]
+    pub fn strict_venues(mut self, strict_venues: bool) -> Self {
+        self.strict_venues = strict_venues;
+        self
+    }
+
+    #[must_use]
+    pub fn strict_publishers(mut self, strict_publishers: bool) -> Self {
+        self.strict_publishers = strict_publishers;
+        self
+    }
+
+    #[must_use]
+    pub fn check_header_publisher(mut self, check_header_publisher: bool) -> Self {
+        self.check_header_publisher = check_header_publisher;
+        self
+    }
+
+    #[must_use]
+    pub fn skip_unsupported(mut self, skip_unsupported: bool) -> Self {
+        self.skip_unsupported = skip_unsupported;
+        self
+    }
+
+    #[must_use]
+    pub fn rtype_filter(mut self, rtypes: &[dbn::RType]) -> Self {
+        self.rtype_filter = rtypes.to_vec();
+        self
+    }
+
+    #[must_use]
+    pub fn instrument_id_cache(mut self, enabled: bool) -> Self {
+        self.instrument_id_cache = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn symbol_map_capacity(mut self, capacity: usize) -> Self {
+        self.symbol_map_capacity = capacity;
+        self
+    }
+
+    #[must_use]
+    pub fn metrics_enabled(mut self, enabled: bool) -> Self {
+        self.metrics_enabled = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn on_new_instrument(
+        mut self,
+        on_new_instrument: impl FnMut(&InstrumentId) + Send + 'static,
+    ) -> Self {
+        self.on_new_instrument = Some(Box::new(on_new_instrument));
+        self
+    }
+
+    /// Validate every venue of both venue maps (once normalized) against the known ISO 10383
+    /// market identifier codes (MICs), so that bad config entries are caught at startup rather
+    /// than on the first record resolving to them.
+    ///
+    /// All invalid entries are returned, publisher entries in map order followed by GLBX
+    /// entries ordered by symbol.
+    pub fn validate_maps(&self) -> Result<(), Vec<InvalidVenueEntry>> {
+        let mut invalid: Vec<InvalidVenueEntry> = self
+            .publisher_venue_map
+            .iter()
+            .map(|(publisher_id, venue)| (*publisher_id, normalize_venue(*venue)))
+            .filter(|(_, venue)| !venue.is_mic())
+            .map(|(publisher_id, venue)| InvalidVenueEntry::Publisher {
+                publisher_id,
+                venue,
+            })
+            .collect();
+
+        let mut glbx_entries: Vec<(Symbol, Venue)> = self
+            .glbx_exchange_map
+            .iter()
+            .map(|(symbol, venue)| (normalize_symbol(*symbol), normalize_venue(*venue)))
+            .filter(|(_, venue)| !venue.is_mic())
+            .collect();
+        glbx_entries.sort_by(|(a, _), (b, _)| a.value.as_str().cmp(b.value.as_str()));
+        invalid.extend(
+            glbx_entries
+                .into_iter()
+                .map(|(symbol, venue)| InvalidVenueEntry::GlbxExchange { symbol, venue }),
+        );
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    #[must_use]
+    pub fn build(self) -> DatabentoSymbologyResolver {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            self.metadata,
+            self.publisher_venue_map,
+            self.glbx_exchange_map,
+        );
+        resolver.set_glbx_venue_entries(self.glbx_venue_entries);
+        resolver.set_venue_overrides(self.venue_overrides);
+        resolver.set_symbol_aliases(self.symbol_aliases);
+        resolver.set_share_class_separator(self.share_class_separator);
+        resolver.set_latest_symbol_names(self.latest_symbol_names);
+        resolver.set_timestamp_source(self.ts_source);
+        resolver.set_date_timezone(self.date_tz);
+        resolver.set_daily_bar_offset(self.daily_bar_offset);
+        if let Some(offset_ns) = self.eod_session_close_offset {
+            resolver.set_eod_session_close_offset(offset_ns);
+        }
+        resolver.set_pre_start_tolerance(self.pre_start_tolerance);
+        resolver.set_zero_ts_as_start(self.zero_ts_as_start);
+        resolver.set_prior_map_window(self.prior_map_window);
+        resolver.set_venue_policy(self.venue_policy);
+        resolver.set_fallback_venue(self.fallback_venue);
+        resolver.set_strict_venues(self.strict_venues);
+        resolver.set_strict_publishers(self.strict_publishers);
+        resolver.set_check_header_publisher(self.check_header_publisher);
+        resolver.set_skip_unsupported(self.skip_unsupported);
+        resolver.set_rtype_filter(&self.rtype_filter);
+        resolver.set_instrument_id_cache(self.instrument_id_cache);
+        resolver.set_symbol_map_capacity(self.symbol_map_capacity);
+        resolver.set_metrics_enabled(self.metrics_enabled);
+        resolver.on_new_instrument = self.on_new_instrument;
+        resolver
+    }
+}
+
+/// Returns the raw symbol with the latest mapping interval for each Databento `instrument_id`
+/// in the metadata.
+///
+/// Only mappings from raw symbols to instrument IDs are considered, other intervals (such as
+/// those with no symbol) are ignored.
+fn latest_raw_symbols(metadata: &dbn::Metadata) -> HashMap<u32, Ustr> {
+    let mut latest: HashMap<u32, (time::Date, Ustr)> = HashMap::new();
+    for mapping in &metadata.mappings {
+        for interval in &mapping.intervals {
+            let Ok(instrument_id) = interval.symbol.parse::<u32>() else {
+                continue;
+            };
+            match latest.entry(instrument_id) {
+                Entry::Occupied(mut entry) if entry.get().0 < interval.end_date => {
+                    entry.insert((interval.end_date, Ustr::from(&mapping.raw_symbol)));
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(entry) => {
+                    entry.insert((interval.end_date, Ustr::from(&mapping.raw_symbol)));
+                }
+            }
+        }
+    }
+
+    latest
+        .into_iter()
+        .map(|(instrument_id, (_, raw_symbol))| (instrument_id, raw_symbol))
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::c_char,
+        sync::{Arc, Mutex},
+    };
+
+    use dbn::{MappingInterval, Metadata, RecordHeader, SType, SymbolMapping, TradeMsg};
+    use nautilus_model::enums::{AssetClass, InstrumentClass};
+    use rstest::rstest;
+    use time::Date;
+    use tracing_test::traced_test;
+
+    use super::*;
+    use crate::databento::stubs::{
+        make_date, make_definition_record, make_esh4_metadata, make_ohlcv_record,
+        make_publisher_venue_map, make_share_class_metadata, make_stat_record, make_symbol_mapping,
+        make_trade_record, TS_2024_01_02,
+    };
+
+    #[rstest]
+    fn test_resolver_builds_symbol_map_once_per_date() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let one_day_ns = 86_400_000_000_000;
+
+        for i in 0..10 {
+            let msg = make_trade_record(5602, 1, TS_2024_01_02 + i);
+            resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        }
+        assert_eq!(resolver.symbol_maps.len(), 1);
+
+        let msg = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        assert_eq!(resolver.symbol_maps.len(), 2);
+    }
+
+    #[rstest]
+    #[case("2024-01")]
+    #[case("2024-13-01")]
+    #[case("not-a-date")]
+    fn test_parse_symbology_date_with_invalid_date_returns_err(#[case] value: &str) {
+        assert!(parse_symbology_date(value).is_err());
+    }
+
+    #[rstest]
+    fn test_resolver_set_metadata_clears_cache() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].raw_symbol = "ESM4".to_string();
+        resolver.set_metadata(metadata);
+
+        assert!(resolver.symbol_maps.is_empty());
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        assert_eq!(instrument_id, InstrumentId::from("ESM4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_resolver_reset_metadata_clears_stale_dates() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.insert_raw_symbol(5603, Ustr::from("NQH4"));
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        assert_eq!(resolver.symbol_map_hits(), 1);
+
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].raw_symbol = "ESM4".to_string();
+        resolver.reset_metadata(metadata);
+
+        assert!(resolver.symbol_maps.is_empty());
+        assert!(resolver.raw_symbols.is_empty());
+        assert_eq!(resolver.symbol_map_hits(), 0);
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        assert_eq!(instrument_id, InstrumentId::from("ESM4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_resolver_instrument_id_cache() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_instrument_id_cache(true);
+        let one_day_ns = 86_400_000_000_000;
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        for _ in 0..3 {
+            let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+            assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        }
+        assert_eq!(resolver.instrument_ids.as_ref().unwrap().len(), 1);
+        assert_eq!(resolver.instrument_ids_date, Some(make_date(2)));
+
+        let msg = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        assert_eq!(resolver.instrument_ids.as_ref().unwrap().len(), 1);
+        assert_eq!(resolver.instrument_ids_date, Some(make_date(3)));
+    }
+
+    #[rstest]
+    fn test_resolver_symbol_map_capacity_evicts_least_recently_used() {
+        let mut resolver = DatabentoSymbologyResolver::builder(make_esh4_metadata())
+            .publisher_venue_map(make_publisher_venue_map())
+            .symbol_map_capacity(2)
+            .build();
+        let one_day_ns = 86_400_000_000_000;
+        let day_1 = make_trade_record(5602, 1, TS_2024_01_02 - one_day_ns);
+        let day_2 = make_trade_record(5602, 1, TS_2024_01_02);
+        let day_3 = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+
+        for msg in [&day_1, &day_2, &day_1, &day_3] {
+            resolver.resolve(&dbn::RecordRef::from(msg), 1).unwrap();
+        }
+
+        // 2024-01-02 was least recently used when 2024-01-03 was inserted
+        assert_eq!(resolver.symbol_maps.len(), 2);
+        assert!(resolver.symbol_maps.contains_key(&make_date(1)));
+        assert!(!resolver.symbol_maps.contains_key(&make_date(2)));
+        assert!(resolver.symbol_maps.contains_key(&make_date(3)));
+        assert_eq!(resolver.symbol_map_hits(), 1);
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_outcome_classifies_error_record_as_control() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let error = dbn::ErrorMsg::new(TS_2024_01_02, "Test error");
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let control = resolver
+            .resolve_outcome(&dbn::RecordRef::from(&error), 1)
+            .unwrap();
+        let instrument = resolver
+            .resolve_outcome(&dbn::RecordRef::from(&trade), 1)
+            .unwrap();
+
+        assert_eq!(
+            control,
+            ResolveOutcome::Control(ControlMsg::Error {
+                message: "Test error".to_string()
+            })
+        );
+        assert_eq!(
+            instrument,
+            ResolveOutcome::Instrument(InstrumentId::from("ESH4.GLBX"))
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_resolve() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[cfg(feature = "latency-histogram")]
+    #[rstest]
+    fn test_resolver_latency_snapshot_counts_resolutions() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+        let unmapped = make_trade_record(9999, 1, TS_2024_01_02);
+
+        for _ in 0..3 {
+            resolver.resolve(&dbn::RecordRef::from(&trade), 1).unwrap();
+        }
+        resolver
+            .resolve(&dbn::RecordRef::from(&unmapped), 1)
+            .unwrap_err();
+
+        assert_eq!(resolver.latency_snapshot().len(), 4);
+    }
+
+    #[rstest]
+    #[traced_test]
+    fn test_resolver_with_pre_start_tolerance_rescues_slightly_early_record() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_pre_start_tolerance(Some(10_000_000_000));
+        let start = resolver.metadata().start;
+        let msg = make_trade_record(5602, 1, start - 3_000_000_000);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert!(logs_contain("precedes metadata start by 3000000000ns"));
+    }
+
+    #[rstest]
+    fn test_resolver_with_pre_start_tolerance_errors_for_wildly_early_record() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_pre_start_tolerance(Some(10_000_000_000));
+        let start = resolver.metadata().start;
+        let one_hour_ns = 3_600_000_000_000;
+        let msg = make_trade_record(5602, 1, start - one_hour_ns);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 1);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::BeforeStart { ts, tolerance: 10_000_000_000, .. } if ts == start - one_hour_ns
+        ));
+    }
+
+    #[rstest]
+    #[case(Some(3), Ok("ESH4.GLBX"))]
+    #[case(Some(1), Err(()))]
+    #[case(None, Err(()))]
+    #[traced_test]
+    fn test_resolver_with_prior_map_window(
+        #[case] window_days: Option<u32>,
+        #[case] expected: Result<&str, ()>,
+    ) {
+        let mut metadata = make_esh4_metadata();
+        // Only mapped on 2024-01-01, leaving a gap on 2024-01-02 and 2024-01-03
+        metadata.mappings[0].intervals[0].end_date = make_date(2);
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        resolver.set_prior_map_window(window_days);
+        let one_day_ns = 86_400_000_000_000;
+        let msg = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 1);
+
+        match expected {
+            Ok(expected) => {
+                assert_eq!(result.unwrap(), InstrumentId::from(expected));
+                assert!(logs_contain(
+                    "Empty symbol map for 2024-01-03, using symbol map for 2024-01-01"
+                ));
+            }
+            Err(()) => assert!(matches!(
+                result.unwrap_err(),
+                SymbologyError::MissingSymbol {
+                    instrument_id: 5602,
+                    ..
+                }
+            )),
+        }
+    }
+
+    #[rstest]
+    fn test_resolver_set_prior_map_window_after_resolve_rebuilds_symbol_maps() {
+        let mut metadata = make_esh4_metadata();
+        // Only mapped on 2024-01-01, leaving a gap on 2024-01-02 and 2024-01-03
+        metadata.mappings[0].intervals[0].end_date = make_date(2);
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let one_day_ns = 86_400_000_000_000;
+        let msg = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+        assert!(resolver.resolve(&dbn::RecordRef::from(&msg), 1).is_err());
+        assert!(resolver.resolve(&dbn::RecordRef::from(&msg), 1).is_err());
+        assert_eq!(resolver.symbol_map_hits(), 1);
+
+        resolver.set_prior_map_window(Some(2));
+
+        assert_eq!(resolver.symbol_map_hits(), 0);
+        assert!(resolver.symbol_maps.is_empty());
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_resolver_from_symbology_json() {
+        let json = r#"{
+            "result": {
+                "ESH4": [{"d0": "2024-01-01", "d1": "2024-01-04", "s": "5602"}]
+            },
+            "symbols": ["ESH4"],
+            "stype_in": "raw_symbol",
+            "stype_out": "instrument_id",
+            "start_date": "2024-01-01",
+            "end_date": "2024-01-04",
+            "partial": [],
+            "not_found": [],
+            "message": "OK",
+            "status": 0
+        }"#;
+        let mut resolver = DatabentoSymbologyResolver::from_symbology_json(json).unwrap();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.XCME"));
+        assert_eq!(resolver.metadata().start, 1_704_067_200_000_000_000);
+        assert_eq!(resolver.metadata().stype_in, Some(SType::RawSymbol));
+    }
+
+    #[rstest]
+    #[case(false, None)]
+    #[case(true, Some("ESH4.GLBX"))]
+    fn test_resolver_zero_ts_as_start(
+        #[case] zero_ts_as_start: bool,
+        #[case] expected: Option<&str>,
+        #[values(TimestampSource::TsRecv, TimestampSource::TsEvent)] ts_source: TimestampSource,
+    ) {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_timestamp_source(ts_source);
+        resolver.set_zero_ts_as_start(zero_ts_as_start);
+        let msg = make_trade_record(5602, 1, 0);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 1);
+
+        match expected {
+            Some(expected) => assert_eq!(result.unwrap(), InstrumentId::from(expected)),
+            None => assert!(result.is_err()),
+        }
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_full_continuous_across_roll() {
+        let mut metadata = make_esh4_metadata();
+        metadata.stype_in = Some(SType::Continuous);
+        metadata.mappings = vec![SymbolMapping {
+            raw_symbol: "ES.c.0".to_string(),
+            intervals: vec![
+                MappingInterval {
+                    start_date: make_date(1),
+                    end_date: make_date(2),
+                    symbol: "5602".to_string(),
+                },
+                MappingInterval {
+                    start_date: make_date(2),
+                    end_date: make_date(4),
+                    symbol: "5603".to_string(),
+                },
+            ],
+        }];
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        resolver.insert_raw_symbol(5602, Ustr::from("ESH4"));
+        resolver.insert_raw_symbol(5603, Ustr::from("ESM4"));
+        let one_day_ns = 86_400_000_000_000;
+
+        let msg1 = make_trade_record(5602, 1, TS_2024_01_02 - one_day_ns);
+        let msg2 = make_trade_record(5603, 1, TS_2024_01_02);
+        let resolved1 = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg1), 1)
+            .unwrap();
+        let resolved2 = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg2), 1)
+            .unwrap();
+
+        assert_eq!(resolved1.instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(resolved1.continuous_symbol, Some(Ustr::from("ES.c.0")));
+        assert_eq!(resolved1.raw_symbol, Ustr::from("ESH4"));
+        assert_eq!(resolved2.instrument_id, InstrumentId::from("ESM4.GLBX"));
+        assert_eq!(resolved2.continuous_symbol, Some(Ustr::from("ES.c.0")));
+
+        // Front month before the roll is no longer mapped after it
+        let msg3 = make_trade_record(5602, 1, TS_2024_01_02);
+        assert!(resolver
+            .resolve_full(&dbn::RecordRef::from(&msg3), 1)
+            .is_err());
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_full_raw_symbol() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let resolved = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+
+        assert_eq!(resolved.instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(resolved.dataset, Some(Ustr::from("GLBX.MDP3")));
+        assert_eq!(resolved.continuous_symbol, None);
+        assert!(!resolved.is_spread);
+        assert_eq!(resolved.leg_count, 1);
+    }
+
+    #[rstest]
+    #[case("GLBX", true)]
+    #[case("GLBX", false)]
+    #[case("XXME", false)]
+    fn test_resolver_resolve_venue_valid(#[case] venue: &str, #[case] strict_venues: bool) {
+        let publisher_venue_map = IndexMap::from([(1, Venue::from(venue))]);
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            publisher_venue_map,
+            HashMap::new(),
+        );
+        resolver.set_strict_venues(strict_venues);
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id.venue, Venue::from(venue));
+    }
+
+    #[rstest]
+    fn test_resolver_builder_applies_all_options() {
+        let new_instruments = Arc::new(Mutex::new(Vec::new()));
+        let hook_instruments = new_instruments.clone();
+        let mut resolver = DatabentoSymbologyResolver::builder(renamed_metadata())
+            .publisher_venue_map(make_publisher_venue_map())
+            .symbol_aliases(HashMap::from([(Ustr::from("ABC"), Ustr::from("ABC24"))]))
+            .date_timezone(DateTimezone::NewYork)
+            .rtype_filter(&[dbn::RType::Mbp0])
+            .metrics_enabled(true)
+            .on_new_instrument(move |instrument_id| {
+                hook_instruments.lock().unwrap().push(*instrument_id);
+            })
+            .build();
+        // 2024-01-03T02:00:00Z is 2024-01-02T21:00:00 in New York, before the rename
+        let trade = make_trade_record(5602, 1, TS_2024_01_02 + 26 * 3_600_000_000_000);
+        let depth = dbn::Mbp10Msg {
+            hd: RecordHeader::new::<dbn::Mbp10Msg>(dbn::rtype::MBP_10, 1, 5602, TS_2024_01_02),
+            ts_recv: TS_2024_01_02,
+            ..Default::default()
+        };
+
+        let instrument_id = resolver
+            .try_resolve(&dbn::RecordRef::from(&trade), 1)
+            .unwrap();
+        let skipped = resolver
+            .try_resolve(&dbn::RecordRef::from(&depth), 1)
+            .unwrap();
+
+        assert_eq!(instrument_id, Some(InstrumentId::from("ABC24.GLBX")));
+        assert_eq!(skipped, None);
+        assert!(resolver.metrics().is_some());
+        assert_eq!(
+            *new_instruments.lock().unwrap(),
+            vec![InstrumentId::from("ABC24.GLBX")]
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_builder_validate_maps() {
+        let builder = DatabentoSymbologyResolver::builder(make_esh4_metadata())
+            .publisher_venue_map(IndexMap::from([
+                (1, Venue::from("GLBX")),
+                (2, Venue::from("XXME")),
+            ]))
+            .glbx_exchange_map(HashMap::from([
+                (Symbol::from("ES"), Venue::from("xcme")),
+                (Symbol::from("ZN"), Venue::from("XCBX")),
+            ]));
+
+        let invalid = builder.validate_maps().unwrap_err();
+
+        assert_eq!(
+            invalid,
+            vec![
+                InvalidVenueEntry::Publisher {
+                    publisher_id: 2,
+                    venue: Venue::from("XXME"),
+                },
+                InvalidVenueEntry::GlbxExchange {
+                    symbol: Symbol::from("ZN"),
+                    venue: Venue::from("XCBX"),
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_builder_validate_maps_with_valid_maps() {
+        let builder = DatabentoSymbologyResolver::builder(make_esh4_metadata())
+            .publisher_venue_map(make_publisher_venue_map())
+            .glbx_exchange_map(HashMap::from([(Symbol::from("ES"), Venue::XCME())]));
+
+        assert!(builder.validate_maps().is_ok());
+    }
+
+    #[rstest]
+    fn test_resolver_builder_resolve_trade() {
+        let mut resolver = DatabentoSymbologyResolver::builder(make_esh4_metadata())
+            .publisher_venue_map(make_publisher_venue_map())
+            .timestamp_source(TimestampSource::TsEvent)
+            .strict_venues(true)
+            .skip_unsupported(true)
+            .instrument_id_cache(true)
+            .build();
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+        let error = dbn::ErrorMsg::new(TS_2024_01_02, "Test error");
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&trade), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(
+            resolver
+                .try_resolve(&dbn::RecordRef::from(&error), 1)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_prewarm() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let one_day_ns = 86_400_000_000_000;
+        let msg1 = make_trade_record(5602, 1, TS_2024_01_02);
+        let msg2 = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+
+        resolver.prewarm(make_date(2), make_date(4)).unwrap();
+        resolver.resolve(&dbn::RecordRef::from(&msg1), 1).unwrap();
+        resolver.resolve(&dbn::RecordRef::from(&msg2), 1).unwrap();
+
+        assert_eq!(resolver.symbol_map_hits(), 2);
+    }
+
+    #[rstest]
+    fn test_resolver_prewarm_outside_range_returns_err() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+
+        let result = resolver.prewarm(make_date(3), make_date(6));
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::PrewarmFailed { dates } if dates == vec![make_date(4), make_date(5)]
+        ));
+    }
+
+    #[rstest]
+    fn test_resolver_metrics() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_metrics_enabled(true);
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+        let stat = make_stat_record(5602, 1, TS_2024_01_02, TS_2024_01_02);
+        let unmapped = make_trade_record(9999, 1, TS_2024_01_02);
+        let unknown_publisher = make_trade_record(5602, 99, TS_2024_01_02);
+        let error = dbn::ErrorMsg::new(TS_2024_01_02, "Test error");
+
+        for (rec_ref, publisher_id) in [
+            (dbn::RecordRef::from(&trade), 1),
+            (dbn::RecordRef::from(&trade), 1),
+            (dbn::RecordRef::from(&stat), 1),
+            (dbn::RecordRef::from(&unmapped), 1),
+            (dbn::RecordRef::from(&unknown_publisher), 99),
+            (dbn::RecordRef::from(&error), 1),
+        ] {
+            let _ = resolver.resolve(&rec_ref, publisher_id);
+        }
+        let snapshot = resolver.metrics().unwrap().snapshot();
+
+        assert_eq!(
+            snapshot.resolved,
+            HashMap::from([
+                ((1, dbn::rtype::MBP_0), 2),
+                ((1, dbn::rtype::STATISTICS), 1)
+            ])
+        );
+        assert_eq!(
+            snapshot.errors,
+            HashMap::from([
+                ("missing_symbol", 1),
+                ("unknown_publisher", 1),
+                ("unsupported_rtype", 1)
+            ])
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_metrics_disabled_by_default() {
+        let resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+
+        assert!(resolver.metrics().is_none());
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_into() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let rec_ref = dbn::RecordRef::from(&msg);
+        let mut out = InstrumentId::from("AAPL.XNAS");
+
+        resolver.resolve_into(&rec_ref, 1, &mut out).unwrap();
+
+        assert_eq!(out, resolver.resolve(&rec_ref, 1).unwrap());
+        assert_eq!(out, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_full_calendar_spread() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].raw_symbol = "ESH4-ESM4".to_string();
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let resolved = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+
+        assert_eq!(resolved.instrument_id, InstrumentId::from("ESH4-ESM4.GLBX"));
+        assert!(resolved.is_spread);
+        assert_eq!(resolved.leg_count, 2);
+    }
+
+    #[rstest]
+    #[case(VenueResolutionPolicy::GlbxFirst, 1, "ESH4.XCME")]
+    #[case(VenueResolutionPolicy::GlbxFirst, 2, "ESH4.XCME")]
+    #[case(VenueResolutionPolicy::PublisherFirst, 1, "ESH4.GLBX")]
+    #[case(VenueResolutionPolicy::PublisherFirst, 2, "ESH4.XNAS")]
+    #[case(VenueResolutionPolicy::GlbxOnlyForGlbxPublishers, 1, "ESH4.XCME")]
+    #[case(VenueResolutionPolicy::GlbxOnlyForGlbxPublishers, 2, "ESH4.XNAS")]
+    fn test_resolver_venue_policy(
+        #[case] venue_policy: VenueResolutionPolicy,
+        #[case] publisher_id: PublisherId,
+        #[case] expected: &str,
+    ) {
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX()), (2, Venue::from("XNAS"))]);
+        let glbx_exchange_map = HashMap::from([(Symbol::from("ESH4"), Venue::XCME())]);
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            publisher_venue_map,
+            glbx_exchange_map,
+        );
+        resolver.set_venue_policy(venue_policy);
+        let msg = make_trade_record(5602, publisher_id, TS_2024_01_02);
+
+        let instrument_id = resolver
+            .resolve(&dbn::RecordRef::from(&msg), publisher_id)
+            .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_fallback_venue_for_unmapped_publisher() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_fallback_venue(Some(Venue::from("UNDEFINED")));
+        let msg = make_trade_record(5602, 99, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 99).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.UNDEFINED"));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_no_fallback_venue_for_unmapped_publisher() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_fallback_venue(None);
+        let msg = make_trade_record(5602, 99, TS_2024_01_02);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 99);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::UnknownPublisher {
+                publisher_id: 99,
+                instrument_id: Some(5602),
+            }
+        ));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_full_symbol_alias() {
+        let glbx_exchange_map = HashMap::from([(Symbol::from("ESH24"), Venue::XCME())]);
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            glbx_exchange_map,
+        );
+        resolver.set_symbol_aliases(HashMap::from([(Ustr::from("ESH4"), Ustr::from("ESH24"))]));
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let resolved = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+
+        assert_eq!(resolved.instrument_id, InstrumentId::from("ESH24.XCME"));
+        assert_eq!(resolved.raw_symbol, Ustr::from("ESH4"));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_symbol_alias_passthrough() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_symbol_aliases(HashMap::from([(Ustr::from("LCO"), Ustr::from("BRN"))]));
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    #[traced_test]
+    fn test_resolver_resolve_span_fields() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert!(logs_contain("Building symbol map for 2024-01-02"));
+        assert!(logs_contain("publisher_id=1"));
+        assert!(logs_contain(&format!("rtype={}", dbn::rtype::MBP_0)));
+        assert!(logs_contain("date=2024-01-02"));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_strict_venues_invalid_mic() {
+        let publisher_venue_map = IndexMap::from([(1, Venue::from("XXME"))]);
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            publisher_venue_map,
+            HashMap::new(),
+        );
+        resolver.set_strict_venues(true);
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 1);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::InvalidVenue { venue, .. } if venue == Venue::from("XXME")
+        ));
+    }
+
+    #[rstest]
+    #[case(false, None)]
+    #[case(true, Some(vec!["ESH4.GLBX", "ESH4.GLBX"]))]
+    fn test_resolver_try_resolve_skip_unsupported(
+        #[case] skip_unsupported: bool,
+        #[case] expected: Option<Vec<&str>>,
+    ) {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_skip_unsupported(skip_unsupported);
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+        let error = dbn::ErrorMsg::new(TS_2024_01_02, "Test error");
+        let records = [
+            dbn::RecordRef::from(&trade),
+            dbn::RecordRef::from(&error),
+            dbn::RecordRef::from(&trade),
+        ];
+
+        let result: Result<Vec<InstrumentId>, SymbologyError> = records
+            .iter()
+            .filter_map(|rec_ref| resolver.try_resolve(rec_ref, 1).transpose())
+            .collect();
+
+        match expected {
+            Some(expected) => {
+                let expected: Vec<InstrumentId> =
+                    expected.into_iter().map(InstrumentId::from).collect();
+                assert_eq!(result.unwrap(), expected);
+            }
+            None => assert!(matches!(
+                result.unwrap_err(),
+                SymbologyError::UnsupportedRType {
+                    rtype: dbn::rtype::ERROR
+                }
+            )),
+        }
+    }
+
+    #[rstest]
+    fn test_resolver_instrument_id_cache_invalidated_on_remap() {
+        let mut metadata = make_esh4_metadata();
+        metadata.stype_in = Some(SType::Continuous);
+        metadata.mappings[0].raw_symbol = "ES.c.0".to_string();
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        resolver.set_instrument_id_cache(true);
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        resolver.insert_raw_symbol(5602, Ustr::from("ESH4"));
+        let instrument_id1 = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        resolver.insert_raw_symbol(5602, Ustr::from("ESM4"));
+        let instrument_id2 = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id1, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(instrument_id2, InstrumentId::from("ESM4.GLBX"));
+    }
+
+    #[rstest]
+    #[case(dbn::rtype::OHLCV_1D)]
+    #[case(dbn::rtype::OHLCV_EOD)]
+    fn test_resolver_daily_bar_offset(#[case] rtype: u8) {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].intervals[0].end_date = make_date(3);
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let one_hour_ns = 3_600_000_000_000;
+        let one_day_ns = 86_400_000_000_000;
+
+        // Session close for the 2024-01-02 trading date at 2024-01-03T00:30:00Z
+        let msg = make_ohlcv_record(rtype, 5602, 1, TS_2024_01_02 + one_day_ns + one_hour_ns / 2);
+        assert!(resolver.resolve(&dbn::RecordRef::from(&msg), 1).is_err());
+
+        resolver.set_daily_bar_offset(one_hour_ns);
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    fn test_resolver_daily_bar_offset_not_applied_to_intraday_bars() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].intervals[0].end_date = make_date(3);
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let one_hour_ns = 3_600_000_000_000;
+        let one_day_ns = 86_400_000_000_000;
+        resolver.set_daily_bar_offset(one_hour_ns);
+
+        let msg = make_ohlcv_record(
+            dbn::rtype::OHLCV_1H,
+            5602,
+            1,
+            TS_2024_01_02 + one_day_ns + one_hour_ns / 2,
+        );
+
+        assert!(resolver.resolve(&dbn::RecordRef::from(&msg), 1).is_err());
+    }
+
+    #[rstest]
+    #[case(TimestampSource::TsRecv, "ESM4.GLBX")]
+    #[case(TimestampSource::TsEvent, "ESH4.GLBX")]
+    fn test_resolver_timestamp_source(#[case] ts_source: TimestampSource, #[case] expected: &str) {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings = vec![
+            SymbolMapping {
+                raw_symbol: "ESH4".to_string(),
+                intervals: vec![MappingInterval {
+                    start_date: make_date(1),
+                    end_date: make_date(2),
+                    symbol: "5602".to_string(),
+                }],
+            },
+            SymbolMapping {
+                raw_symbol: "ESM4".to_string(),
+                intervals: vec![MappingInterval {
+                    start_date: make_date(2),
+                    end_date: make_date(4),
+                    symbol: "5602".to_string(),
+                }],
+            },
+        ];
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        resolver.set_timestamp_source(ts_source);
+
+        // Event on 2024-01-01 received on 2024-01-02
+        let mut msg = make_trade_record(5602, 1, TS_2024_01_02);
+        msg.hd.ts_event = TS_2024_01_02 - 1;
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    #[case('F', "FFIXSX", AssetClass::Index, InstrumentClass::Future)] // Index future
+    #[case('C', "OCAFPS", AssetClass::Commodity, InstrumentClass::Option)]
+    #[case('K', "ESXXXX", AssetClass::Equity, InstrumentClass::Spot)]
+    fn test_resolve_full_definition_classes(
+        #[case] code: char,
+        #[case] cfi: &str,
+        #[case] expected_asset_class: AssetClass,
+        #[case] expected_instrument_class: InstrumentClass,
+    ) {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_definition_record(5602, 1, code, cfi);
+
+        let resolved = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+
+        assert_eq!(resolved.instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(resolved.asset_class, Some(expected_asset_class));
+        assert_eq!(resolved.instrument_class, Some(expected_instrument_class));
+    }
+
+    #[rstest]
+    fn test_resolve_full_market_data_has_no_classes() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let resolved = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+
+        assert_eq!(resolved.asset_class, None);
+        assert_eq!(resolved.instrument_class, None);
+    }
+
+    #[rstest]
+    fn test_resolve_full_definition_unknown_class() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_definition_record(5602, 1, 'Z', "");
+
+        let resolved = resolver
+            .resolve_full(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+
+        assert_eq!(resolved.instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(resolved.asset_class, None);
+        assert_eq!(resolved.instrument_class, None);
+    }
+
+    fn renamed_metadata() -> Metadata {
+        let mapping = |raw_symbol: &str, start_day: u8, end_day: u8| SymbolMapping {
+            raw_symbol: raw_symbol.to_string(),
+            intervals: vec![MappingInterval {
+                start_date: make_date(start_day),
+                end_date: make_date(end_day),
+                symbol: "5602".to_string(),
+            }],
+        };
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings = vec![mapping("ABC", 1, 3), mapping("ABCD", 3, 4)];
+        metadata
+    }
+
+    #[rstest]
+    #[case(false, "ABC.GLBX", "ABCD.GLBX")]
+    #[case(true, "ABCD.GLBX", "ABCD.GLBX")]
+    fn test_resolver_latest_symbol_names_across_rename(
+        #[case] latest_symbol_names: bool,
+        #[case] expected_before: &str,
+        #[case] expected_after: &str,
+    ) {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            renamed_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_latest_symbol_names(latest_symbol_names);
+        let one_day_ns = 86_400_000_000_000;
+        let before = make_trade_record(5602, 1, TS_2024_01_02);
+        let after = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+
+        let before_id = resolver.resolve(&dbn::RecordRef::from(&before), 1).unwrap();
+        let after_id = resolver.resolve(&dbn::RecordRef::from(&after), 1).unwrap();
+
+        assert_eq!(before_id, InstrumentId::from(expected_before));
+        assert_eq!(after_id, InstrumentId::from(expected_after));
+    }
+
+    #[rstest]
+    #[case::utc(DateTimezone::Utc, "ABCD.GLBX")]
+    #[case::new_york(DateTimezone::NewYork, "ABC.GLBX")]
+    #[case::fixed(DateTimezone::Fixed(time::UtcOffset::from_hms(-5, 0, 0).unwrap()), "ABC.GLBX")]
+    fn test_resolver_date_timezone(#[case] date_tz: DateTimezone, #[case] expected: &str) {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            renamed_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_date_timezone(date_tz);
+        // 2024-01-03T02:00:00Z is 2024-01-02T21:00:00 in New York
+        let msg = make_trade_record(5602, 1, TS_2024_01_02 + 26 * 3_600_000_000_000);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_tbbo_by_ts_recv() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            renamed_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let one_day_ns = 86_400_000_000_000;
+        let msg = dbn::TbboMsg {
+            hd: RecordHeader::new::<dbn::TbboMsg>(
+                dbn::rtype::MBP_1,
+                1,
+                5602,
+                TS_2024_01_02 + one_day_ns - 1,
+            ),
+            action: 'T' as c_char,
+            ts_recv: TS_2024_01_02 + one_day_ns,
+            ..Default::default()
+        };
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ABCD.GLBX"));
+    }
+
+    struct FixedSymbolMapProvider;
+
+    impl SymbolMapProvider for FixedSymbolMapProvider {
+        fn symbol_map_for_date(&self, _date: Date) -> Result<PitSymbolMap, SymbologyError> {
+            let mut symbol_map = PitSymbolMap::new();
+            symbol_map.inner_mut().insert(42, "CUSTOM".to_string());
+            Ok(symbol_map)
+        }
+    }
+
+    #[rstest]
+    fn test_resolver_with_symbol_map_provider() {
+        let mut resolver = DatabentoSymbologyResolver::with_symbol_map_provider(
+            make_esh4_metadata(),
+            FixedSymbolMapProvider,
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let custom = make_trade_record(42, 1, TS_2024_01_02);
+        let es = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&custom), 1).unwrap();
+        let result = resolver.resolve(&dbn::RecordRef::from(&es), 1);
+
+        assert_eq!(instrument_id, InstrumentId::from("CUSTOM.GLBX"));
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::MissingSymbol {
+                instrument_id: 5602,
+                ..
+            }
+        ));
+    }
+
+    #[rstest]
+    fn test_metadata_symbol_map_provider() {
+        let symbol_map =
+            SymbolMapProvider::symbol_map_for_date(&make_esh4_metadata(), make_date(2)).unwrap();
+
+        assert_eq!(symbol_map.get(5602).map(String::as_str), Some("ESH4"));
+    }
+
+    #[rstest]
+    #[case(TimestampSource::TsRecv)]
+    #[case(TimestampSource::TsEvent)]
+    fn test_resolver_resolves_ohlcv_by_ts_event(#[case] ts_source: TimestampSource) {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            renamed_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_timestamp_source(ts_source);
+        // The last hourly bar of 2024-01-02, any receive time of which would be on 2024-01-03
+        let ts_event = TS_2024_01_02 + 23 * 3_600_000_000_000;
+        let msg = make_ohlcv_record(dbn::rtype::OHLCV_1H, 5602, 1, ts_event);
+        let rec_ref = dbn::RecordRef::from(&msg);
+
+        let (_, ts) = decode_instrument_id_and_ts(&rec_ref, ts_source, 0, 0, 0).unwrap();
+        let instrument_id = resolver.resolve(&rec_ref, 1).unwrap();
+
+        assert_eq!(ts, ts_event);
+        assert_eq!(instrument_id, InstrumentId::from("ABC.GLBX"));
+    }
+
+    #[rstest]
+    fn test_resolver_interns_each_symbol_once_per_date() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let first = make_trade_record(5602, 1, TS_2024_01_02);
+        let second = make_trade_record(5602, 1, TS_2024_01_02 + 1);
+
+        let first = resolver
+            .resolve_full(&dbn::RecordRef::from(&first), 1)
+            .unwrap();
+        let second = resolver
+            .resolve_full(&dbn::RecordRef::from(&second), 1)
+            .unwrap();
+
+        assert_eq!(resolver.symbol_interns(), 1);
+        assert_eq!(
+            first.raw_symbol.as_char_ptr(),
+            second.raw_symbol.as_char_ptr()
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_on_new_instrument_called_once_per_instrument() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings.push(make_symbol_mapping("NQH4", 5603));
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = seen.clone();
+        resolver.set_on_new_instrument(move |instrument_id| {
+            hook_seen.lock().unwrap().push(*instrument_id);
+        });
+        let msgs: Vec<TradeMsg> = [5602, 5603, 5602, 9999, 5603, 5602]
+            .into_iter()
+            .map(|instrument_id| make_trade_record(instrument_id, 1, TS_2024_01_02))
+            .collect();
+
+        for msg in &msgs {
+            let _ = resolver.resolve(&dbn::RecordRef::from(msg), 1);
+        }
+        resolver.resolve_record(&msgs[1], 1).unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                InstrumentId::from("ESH4.GLBX"),
+                InstrumentId::from("NQH4.GLBX"),
+            ]
+        );
+        assert_eq!(resolver.distinct_instrument_count(), 2);
+    }
+
+    #[rstest]
+    fn test_resolver_on_new_instrument_called_again_after_reset_metadata() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let calls = Arc::new(Mutex::new(0));
+        let hook_calls = calls.clone();
+        resolver.set_on_new_instrument(move |_| {
+            *hook_calls.lock().unwrap() += 1;
+        });
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        resolver.reset_metadata(make_esh4_metadata());
+        assert_eq!(resolver.distinct_instrument_count(), 0);
+        resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert_eq!(resolver.distinct_instrument_count(), 1);
+    }
+
+    #[rstest]
+    #[case(1, Ok("ESH4.GLBX"))]
+    #[case(2, Err(()))]
+    fn test_resolver_check_header_publisher(
+        #[case] publisher_id: PublisherId,
+        #[case] expected: Result<&str, ()>,
+    ) {
+        let mut resolver = DatabentoSymbologyResolver::builder(make_esh4_metadata())
+            .publisher_venue_map(IndexMap::from([
+                (1, Venue::GLBX()),
+                (2, Venue::from("XNAS")),
+            ]))
+            .check_header_publisher(true)
+            .build();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), publisher_id);
+        let owned_result = resolver.resolve_record(&msg, publisher_id);
+
+        match expected {
+            Ok(expected) => {
+                assert_eq!(result.unwrap(), InstrumentId::from(expected));
+                assert_eq!(owned_result.unwrap(), InstrumentId::from(expected));
+            }
+            Err(()) => {
+                for result in [result, owned_result] {
+                    assert!(matches!(
+                        result.unwrap_err(),
+                        SymbologyError::PublisherMismatch {
+                            publisher_id: 2,
+                            header_publisher_id: 1,
+                        }
+                    ));
+                }
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_from_header() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            IndexMap::from([(1, Venue::GLBX()), (2, Venue::from("XNAS"))]),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 2, TS_2024_01_02);
+
+        let instrument_id = resolver
+            .resolve_from_header(&dbn::RecordRef::from(&msg))
+            .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.XNAS"));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_grouped() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings.push(make_symbol_mapping("CLH4", 5604));
+        let glbx_exchange_map = HashMap::from([
+            (Symbol::from("ES"), Venue::XCME()),
+            (Symbol::from("CL"), Venue::from("XNYM")),
+        ]);
+        let mut resolver = DatabentoSymbologyResolver::new(
+            metadata,
+            make_publisher_venue_map(),
+            glbx_exchange_map,
+        );
+        let msgs: Vec<TradeMsg> = [5602, 5604, 9999, 5602]
+            .into_iter()
+            .map(|instrument_id| make_trade_record(instrument_id, 1, TS_2024_01_02))
+            .collect();
+        let records: Vec<dbn::RecordRef> = msgs.iter().map(dbn::RecordRef::from).collect();
+
+        let (grouped, errors) = resolver.resolve_grouped(&records);
+
+        let es = InstrumentId::from("ESH4.XCME");
+        let cl = InstrumentId::from("CLH4.XNYM");
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[&Venue::XCME()], vec![(es, 0), (es, 3)]);
+        assert_eq!(grouped[&Venue::from("XNYM")], vec![(cl, 1)]);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            SymbologyError::Record { index: 2, source }
+                if matches!(**source, SymbologyError::MissingSymbol { instrument_id: 9999, .. })
+        ));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_sorted_is_deterministic() {
+        let mut metadata = make_esh4_metadata();
+        for (raw_symbol, instrument_id) in [("NQH4", 5603), ("CLH4", 5604)] {
+            metadata
+                .mappings
+                .push(make_symbol_mapping(raw_symbol, instrument_id));
+        }
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let msgs: Vec<TradeMsg> = [5604, 5602, 5603, 5602, 5604]
+            .into_iter()
+            .map(|instrument_id| make_trade_record(instrument_id, 1, TS_2024_01_02))
+            .collect();
+        let records: Vec<dbn::RecordRef> = msgs.iter().map(dbn::RecordRef::from).collect();
+        let shuffled: Vec<dbn::RecordRef> = [3, 0, 4, 2, 1].iter().map(|i| records[*i]).collect();
+
+        let first = resolver.resolve_sorted(&records).unwrap();
+        let second = resolver.resolve_sorted(&shuffled).unwrap();
+
+        assert_eq!(
+            first,
+            vec![
+                InstrumentId::from("ESH4.GLBX"),
+                InstrumentId::from("ESH4.GLBX"),
+                InstrumentId::from("NQH4.GLBX"),
+                InstrumentId::from("CLH4.GLBX"),
+                InstrumentId::from("CLH4.GLBX"),
+            ]
+        );
+        assert_eq!(second, first);
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_sorted_keeps_duplicates() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msgs: Vec<TradeMsg> = (0..3)
+            .map(|_| make_trade_record(5602, 1, TS_2024_01_02))
+            .collect();
+        let records: Vec<dbn::RecordRef> = msgs.iter().map(dbn::RecordRef::from).collect();
+
+        let resolved = resolver.resolve_sorted(&records).unwrap();
+
+        assert_eq!(resolved, vec![InstrumentId::from("ESH4.GLBX"); 3]);
+    }
+
+    #[rstest]
+    #[case(VenueResolutionPolicy::GlbxFirst)]
+    #[case(VenueResolutionPolicy::PublisherFirst)]
+    fn test_resolver_venue_override_wins(#[case] venue_policy: VenueResolutionPolicy) {
+        let mut resolver = DatabentoSymbologyResolver::builder(make_esh4_metadata())
+            .publisher_venue_map(make_publisher_venue_map())
+            .glbx_exchange_map(HashMap::from([(Symbol::from("ESH4"), Venue::XCME())]))
+            .venue_overrides(HashMap::from([(
+                (1, Symbol::from("ESH4")),
+                Venue::from("XCBT"),
+            )]))
+            .venue_policy(venue_policy)
+            .build();
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let other_publisher = make_trade_record(5602, 2, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        let other_instrument_id = resolver
+            .resolve(&dbn::RecordRef::from(&other_publisher), 2)
+            .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.XCBT"));
+        assert_eq!(other_instrument_id, InstrumentId::from("ESH4.XCME"));
+    }
+
+    #[rstest]
+    fn test_resolver_glbx_venue_entries_by_publisher() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            IndexMap::from([(1, Venue::GLBX()), (2, Venue::GLBX())]),
+            HashMap::new(),
+        );
+        resolver.set_glbx_venue_entries(HashMap::from([(
+            Symbol::from("ES"),
+            GlbxVenueEntry {
+                default: Venue::XCME(),
+                by_publisher: HashMap::from([(2, Venue::from("xcbt"))]),
+            },
+        )]));
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+        let other_publisher = make_trade_record(5602, 2, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+        let other_instrument_id = resolver
+            .resolve(&dbn::RecordRef::from(&other_publisher), 2)
+            .unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.XCME"));
+        assert_eq!(other_instrument_id, InstrumentId::from("ESH4.XCBT"));
+    }
+
+    #[rstest]
+    #[case(ShareClassSeparator::None, ["BRKB", "BRKB", "BRKB", "BRKB"])]
+    #[case(ShareClassSeparator::Dot, ["BRK.B", "BRK.B", "BRK.B", "BRKB"])]
+    #[case(ShareClassSeparator::Slash, ["BRK/B", "BRK/B", "BRK/B", "BRKB"])]
+    fn test_resolver_share_class_separator(
+        #[case] separator: ShareClassSeparator,
+        #[case] expected: [&str; 4],
+    ) {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_share_class_metadata(),
+            IndexMap::from([(2, Venue::from("XNAS"))]),
+            HashMap::new(),
+        );
+        resolver.set_share_class_separator(Some(separator));
+
+        for (instrument_id, expected) in (1..).zip(expected) {
+            let msg = make_trade_record(instrument_id, 2, TS_2024_01_02);
+
+            let resolved = resolver.resolve(&dbn::RecordRef::from(&msg), 2).unwrap();
+
+            assert_eq!(resolved.symbol, Symbol::from(expected));
+        }
+    }
+
+    #[rstest]
+    fn test_resolver_share_class_separator_ignores_non_equity_publishers() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_share_class_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_share_class_separator(Some(ShareClassSeparator::None));
+        let msg = make_trade_record(2, 1, TS_2024_01_02);
+
+        let resolved = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(resolved.symbol, Symbol::from("BRK/B"));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_mbp10() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let mut depth = dbn::Mbp10Msg {
+            hd: RecordHeader::new::<dbn::Mbp10Msg>(dbn::rtype::MBP_10, 1, 5602, TS_2024_01_02),
+            ts_recv: TS_2024_01_02,
+            ..Default::default()
+        };
+        depth.levels[0].bid_px = 4_750_250_000_000;
+        depth.levels[9].ask_px = 4_753_000_000_000;
+        let rec_ref = dbn::RecordRef::from(&depth);
+
+        let (instrument_id, msg) = resolver.resolve_mbp10(&rec_ref, 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(msg.levels, depth.levels);
+        assert!(std::ptr::eq(msg, &depth));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_mbp10_with_trade_returns_err() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let result = resolver.resolve_mbp10(&dbn::RecordRef::from(&trade), 1);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SymbologyError::UnsupportedRType {
+                rtype: dbn::rtype::MBP_0
+            }
+        ));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_record_owned_trade() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            renamed_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let one_day_ns = 86_400_000_000_000;
+        let msg = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+
+        let instrument_id = resolver.resolve_record(&msg, 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ABCD.GLBX"));
+        assert_eq!(
+            instrument_id,
+            resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap()
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_record_owned_ohlcv() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            renamed_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_ohlcv_record(dbn::rtype::OHLCV_1H, 5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve_record(&msg, 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ABC.GLBX"));
+    }
+
+    #[rstest]
+    fn test_resolver_normalizes_venue_maps() {
+        let publisher_venue_map = IndexMap::from([(1, Venue::from("xcme "))]);
+        let glbx_exchange_map = HashMap::from([(Symbol::from("es "), Venue::from(" Xcme"))]);
+        let mut publisher_resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            publisher_venue_map,
+            HashMap::new(),
+        );
+        let mut glbx_resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            IndexMap::new(),
+            glbx_exchange_map,
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let from_publisher = publisher_resolver
+            .resolve(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+        let from_glbx = glbx_resolver
+            .resolve(&dbn::RecordRef::from(&msg), 1)
+            .unwrap();
+
+        assert_eq!(from_publisher, InstrumentId::from("ESH4.XCME"));
+        assert_eq!(from_glbx, from_publisher);
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_all_does_not_abort() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+        let unmapped = make_trade_record(9999, 1, TS_2024_01_02);
+        let unknown_publisher = make_trade_record(5602, 99, TS_2024_01_02);
+        let error = dbn::ErrorMsg::new(TS_2024_01_02, "Test error");
+        let records = [
+            dbn::RecordRef::from(&trade),
+            dbn::RecordRef::from(&unmapped),
+            dbn::RecordRef::from(&error),
+            dbn::RecordRef::from(&unknown_publisher),
+            dbn::RecordRef::from(&trade),
+        ];
+
+        let results = resolver.resolve_all(&records);
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &InstrumentId::from("ESH4.GLBX")
+        );
+        assert!(matches!(
+            results[1],
+            Err(SymbologyError::MissingSymbol {
+                instrument_id: 9999,
+                ..
+            })
+        ));
+        assert!(matches!(
+            results[2],
+            Err(SymbologyError::UnsupportedRType { .. })
+        ));
+        assert!(matches!(
+            results[3],
+            Err(SymbologyError::UnknownPublisher {
+                publisher_id: 99,
+                ..
+            })
+        ));
+        assert_eq!(
+            results[4].as_ref().unwrap(),
+            &InstrumentId::from("ESH4.GLBX")
+        );
+    }
+
+    #[rstest]
+    fn test_resolver_eod_session_close_offset() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings[0].intervals[0].end_date = make_date(3);
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let one_hour_ns = 3_600_000_000_000;
+        let one_day_ns = 86_400_000_000_000;
+        resolver.set_daily_bar_offset(one_hour_ns / 4);
+        resolver.set_eod_session_close_offset(21 * one_hour_ns);
+
+        // EOD bar for the 2024-01-02 session (closing 21:00Z) emitted at 2024-01-03T00:30:00Z
+        let eod = make_ohlcv_record(
+            dbn::rtype::OHLCV_EOD,
+            5602,
+            1,
+            TS_2024_01_02 + one_day_ns + one_hour_ns / 2,
+        );
+        let daily = make_ohlcv_record(
+            dbn::rtype::OHLCV_1D,
+            5602,
+            1,
+            TS_2024_01_02 + one_day_ns + one_hour_ns / 2,
+        );
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&eod), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+        assert!(resolver.resolve(&dbn::RecordRef::from(&daily), 1).is_err());
+    }
+
+    #[rstest]
+    #[case(false, true)]
+    #[case(true, false)]
+    fn test_resolver_strict_publishers(#[case] strict_publishers: bool, #[case] expected_ok: bool) {
+        // XNAS.ITCH publisher foreign to the GLBX.MDP3 metadata, mapped so resolution succeeds
+        let publisher_venue_map = IndexMap::from([(1, Venue::GLBX()), (2, Venue::from("XNAS"))]);
+        let mut resolver = DatabentoSymbologyResolver::builder(make_esh4_metadata())
+            .publisher_venue_map(publisher_venue_map)
+            .strict_publishers(strict_publishers)
+            .build();
+        let msg = make_trade_record(5602, 2, TS_2024_01_02);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 2);
+
+        assert_eq!(result.is_ok(), expected_ok);
+        if !expected_ok {
+            let err = result.unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "`publisher_id` 2 is not a publisher of the metadata `dataset` GLBX.MDP3"
+            );
+            assert!(matches!(
+                err,
+                SymbologyError::ForeignPublisher {
+                    publisher_id: 2,
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[rstest]
+    fn test_resolver_strict_publishers_with_dataset_publisher() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_strict_publishers(true);
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from("ESH4.GLBX"));
+    }
+
+    #[rstest]
+    #[case(SType::RawSymbol, SType::InstrumentId, "ESH4", "5602", "ESH4.GLBX")]
+    #[case(SType::Parent, SType::InstrumentId, "ES.FUT", "5602", "ES.FUT.GLBX")]
+    #[case(
+        SType::Continuous,
+        SType::InstrumentId,
+        "ES.c.0",
+        "5602",
+        "ES.c.0.GLBX"
+    )]
+    #[case(SType::InstrumentId, SType::RawSymbol, "5602", "ESH4", "ESH4.GLBX")]
+    fn test_resolver_stype_pairings(
+        #[case] stype_in: SType,
+        #[case] stype_out: SType,
+        #[case] mapping_symbol: &str,
+        #[case] interval_symbol: &str,
+        #[case] expected: &str,
+    ) {
+        let mut metadata = make_esh4_metadata();
+        metadata.stype_in = Some(stype_in);
+        metadata.stype_out = stype_out;
+        metadata.mappings = vec![SymbolMapping {
+            raw_symbol: mapping_symbol.to_string(),
+            intervals: vec![MappingInterval {
+                start_date: make_date(1),
+                end_date: make_date(4),
+                symbol: interval_symbol.to_string(),
+            }],
+        }];
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let instrument_id = resolver.resolve(&dbn::RecordRef::from(&msg), 1).unwrap();
+
+        assert_eq!(instrument_id, InstrumentId::from(expected));
+    }
+
+    #[rstest]
+    fn test_resolver_unsupported_stype_pairing() {
+        let mut metadata = make_esh4_metadata();
+        metadata.stype_out = SType::RawSymbol;
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let result = resolver.resolve(&dbn::RecordRef::from(&msg), 1);
+
+        assert!(matches!(
+            result,
+            Err(SymbologyError::UnsupportedSTypes {
+                stype_in: Some(SType::RawSymbol),
+                stype_out: SType::RawSymbol,
+            })
+        ));
+    }
+
+    #[rstest]
+    fn test_resolver_resolve_formatted() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        let msg = make_trade_record(5602, 1, TS_2024_01_02);
+
+        let formatted = resolver
+            .resolve_formatted(&dbn::RecordRef::from(&msg), 1, IdFormat::VenueColonSymbol)
+            .unwrap();
+
+        assert_eq!(formatted, "GLBX:ESH4");
+    }
+
+    #[rstest]
+    #[traced_test]
+    fn test_resolver_intraday_duplicate_instrument_id_last_wins() {
+        let mut metadata = make_esh4_metadata();
+        metadata.mappings.push(SymbolMapping {
+            raw_symbol: "ESH4.R".to_string(),
+            intervals: vec![MappingInterval {
+                start_date: make_date(2),
+                end_date: make_date(3),
+                symbol: "5602".to_string(),
+            }],
+        });
+        let mut resolver =
+            DatabentoSymbologyResolver::new(metadata, make_publisher_venue_map(), HashMap::new());
+        let one_day_ns = 86_400_000_000_000;
+        let duplicated = make_trade_record(5602, 1, TS_2024_01_02);
+        let unique = make_trade_record(5602, 1, TS_2024_01_02 + one_day_ns);
+
+        let duplicated_id = resolver
+            .resolve(&dbn::RecordRef::from(&duplicated), 1)
+            .unwrap();
+        let unique_id = resolver.resolve(&dbn::RecordRef::from(&unique), 1).unwrap();
+
+        assert_eq!(duplicated_id, InstrumentId::from("ESH4.R.GLBX"));
+        assert_eq!(unique_id, InstrumentId::from("ESH4.GLBX"));
+        assert!(logs_contain("Duplicate `instrument_id` 5602 on 2024-01-02"));
+    }
+
+    #[rstest]
+    fn test_resolver_rtype_filter() {
+        let mut resolver = DatabentoSymbologyResolver::new(
+            make_esh4_metadata(),
+            make_publisher_venue_map(),
+            HashMap::new(),
+        );
+        resolver.set_rtype_filter(&[dbn::RType::Mbp0]);
+        let trade = make_trade_record(5602, 1, TS_2024_01_02);
+        let depth = dbn::Mbp10Msg {
+            hd: RecordHeader::new::<dbn::Mbp10Msg>(dbn::rtype::MBP_10, 1, 5602, TS_2024_01_02),
+            ts_recv: TS_2024_01_02,
+            ..Default::default()
+        };
+        // Unmapped, so would be an error if resolved
+        let definition = make_definition_record(9999, 1, 'F', "FFIXSX");
+        let records = [
+            dbn::RecordRef::from(&trade),
+            dbn::RecordRef::from(&depth),
+            dbn::RecordRef::from(&definition),
+            dbn::RecordRef::from(&trade),
+        ];
+
+        let results: Vec<Option<InstrumentId>> = records
+            .iter()
+            .map(|rec_ref| resolver.try_resolve(rec_ref, 1).unwrap())
+            .collect();
+
+        let trade_id = Some(InstrumentId::from("ESH4.GLBX"));
+        assert_eq!(results, vec![trade_id, None, None, trade_id]);
+        assert_eq!(resolver.symbol_map_hits(), 1);
+    }
+}
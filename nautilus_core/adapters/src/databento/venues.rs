@@ -0,0 +1,669 @@
+// -------------------------------------------------------------------------------------------------
+//  Copyright (C) 2015-2024 Nautech Systems Pty Ltd. All rights reserved.
+//  https://nautechsystems.io
+//
+//  Licensed under the GNU Lesser General Public License Version 3.0 (the "License");
+//  You may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at https://www.gnu.org/licenses/lgpl-3.0.en.html
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+// -------------------------------------------------------------------------------------------------
+
+use std::{collections::HashMap, fs, io::Read, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use indexmap::IndexMap;
+use nautilus_model::identifiers::{symbol::Symbol, venue::Venue};
+use serde::{Deserialize, Serialize};
+
+use super::types::PublisherId;
+
+/// The bundled CME Globex product to exchange assignments.
+const GLBX_EXCHANGES_JSON: &str = include_str!("glbx_exchanges.json");
+
+/// The bundled Databento publishers listing.
+const PUBLISHERS_JSON: &str = include_str!("publishers.json");
+
+/// Represents a single row of the Databento publishers listing.
+#[derive(Clone, Debug, Deserialize)]
+struct PublisherRow {
+    publisher_id: PublisherId,
+    #[serde(default)]
+    dataset: String,
+    venue: String,
+}
+
+/// Represents a single CME Globex symbol to exchange venue assignment.
+#[derive(Clone, Debug, Deserialize)]
+struct GlbxExchangeRow {
+    symbol: String,
+    venue: String,
+}
+
+/// Represents the venue maps used for symbology resolution, as shipped in deployment config.
+///
+/// The order of the `publisher_venue_map` is preserved on round-trip.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VenueMaps {
+    pub publisher_venue_map: IndexMap<PublisherId, Venue>,
+    pub glbx_exchange_map: HashMap<Symbol, Venue>,
+}
+
+impl VenueMaps {
+    /// Return the venue maps with all venues and GLBX symbols normalized (see `normalize_venue`).
+    #[must_use]
+    pub fn normalized(self) -> Self {
+        Self {
+            publisher_venue_map: normalize_publisher_venue_map(self.publisher_venue_map),
+            glbx_exchange_map: normalize_glbx_exchange_map(self.glbx_exchange_map),
+        }
+    }
+}
+
+/// Represents the venue of a GLBX exchange map symbol, for symbols which legitimately appear
+/// under several GLBX sub-venues depending on the publisher.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlbxVenueEntry {
+    /// The venue for publishers without a specific venue.
+    pub default: Venue,
+    /// The venues for specific publishers, which take precedence over the default.
+    pub by_publisher: HashMap<PublisherId, Venue>,
+}
+
+impl GlbxVenueEntry {
+    /// Return the venue for the given `publisher_id`.
+    #[must_use]
+    pub fn venue(&self, publisher_id: PublisherId) -> Venue {
+        self.by_publisher
+            .get(&publisher_id)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Return the entry with all venues normalized (see `normalize_venue`).
+    #[must_use]
+    pub fn normalized(self) -> Self {
+        Self {
+            default: normalize_venue(self.default),
+            by_publisher: self
+                .by_publisher
+                .into_iter()
+                .map(|(publisher_id, venue)| (publisher_id, normalize_venue(venue)))
+                .collect(),
+        }
+    }
+}
+
+impl From<Venue> for GlbxVenueEntry {
+    fn from(venue: Venue) -> Self {
+        Self {
+            default: venue,
+            by_publisher: HashMap::new(),
+        }
+    }
+}
+
+/// Returns the given `venue` with surrounding whitespace trimmed and converted to uppercase,
+/// so that map entries such as `"xcme "` and `"XCME"` are the same `Venue`.
+#[must_use]
+pub fn normalize_venue(venue: Venue) -> Venue {
+    match normalize_code(venue.value.as_str()) {
+        Some(value) => Venue::from_str_unchecked(&value),
+        None => venue,
+    }
+}
+
+/// Returns the given `symbol` with surrounding whitespace trimmed and converted to uppercase,
+/// as for `normalize_venue`.
+#[must_use]
+pub fn normalize_symbol(symbol: Symbol) -> Symbol {
+    match normalize_code(symbol.value.as_str()) {
+        Some(value) => Symbol::from_str_unchecked(&value),
+        None => symbol,
+    }
+}
+
+/// Returns the given publisher venue map with all venues normalized.
+#[must_use]
+pub fn normalize_publisher_venue_map(
+    publisher_venue_map: IndexMap<PublisherId, Venue>,
+) -> IndexMap<PublisherId, Venue> {
+    publisher_venue_map
+        .into_iter()
+        .map(|(publisher_id, venue)| (publisher_id, normalize_venue(venue)))
+        .collect()
+}
+
+/// Returns the given GLBX exchange map with all symbols and venues normalized.
+///
+/// If several symbols normalize to the same symbol then the venue of the last is kept.
+#[must_use]
+pub fn normalize_glbx_exchange_map(
+    glbx_exchange_map: HashMap<Symbol, Venue>,
+) -> HashMap<Symbol, Venue> {
+    glbx_exchange_map
+        .into_iter()
+        .map(|(symbol, venue)| (normalize_symbol(symbol), normalize_venue(venue)))
+        .collect()
+}
+
+/// Load the venue maps from the JSON file at the given `path`, normalizing all venues and
+/// GLBX symbols.
+pub fn load_maps_from_json(path: &Path) -> Result<VenueMaps> {
+    // Read to a string first, as the identifiers deserialize from borrowed strings
+    let content = fs::read_to_string(path)?;
+    let maps: VenueMaps = serde_json::from_str(&content)?;
+    Ok(maps.normalized())
+}
+
+/// Save the venue maps as JSON to the file at the given `path`.
+pub fn save_maps_to_json(maps: &VenueMaps, path: &Path) -> Result<()> {
+    let content = serde_json::to_string_pretty(maps)?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Load a map of CME Globex symbols to exchange venues from the file at the given `path`.
+///
+/// Files with a `.csv` extension are parsed as `symbol,venue` rows (with an optional header),
+/// otherwise the file is parsed as JSON in the same format as the bundled default.
+pub fn load_glbx_exchange_map(path: &Path) -> Result<HashMap<Symbol, Venue>> {
+    let content = fs::read_to_string(path)?;
+    let rows = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => parse_glbx_exchange_csv(&content)?,
+        _ => serde_json::from_str(&content)?,
+    };
+    build_glbx_exchange_map(rows)
+}
+
+/// Return the map of CME Globex product symbols to exchange venues bundled with the adapter.
+#[must_use]
+pub fn default_glbx_exchange_map() -> HashMap<Symbol, Venue> {
+    // SAFETY: Bundled table is known to be valid
+    let rows = serde_json::from_str(GLBX_EXCHANGES_JSON).expect("Invalid bundled GLBX exchanges");
+    build_glbx_exchange_map(rows).expect("Invalid bundled GLBX exchanges")
+}
+
+/// Return the map of Databento publisher IDs to venues from the publishers listing bundled
+/// with the adapter.
+#[must_use]
+pub fn build_publisher_venue_map() -> IndexMap<PublisherId, Venue> {
+    // SAFETY: Bundled table is known to be valid
+    build_publisher_venue_map_from_reader(PUBLISHERS_JSON.as_bytes())
+        .expect("Invalid bundled publishers")
+}
+
+/// Build a map of Databento publisher IDs to venues from a JSON publishers listing in the same
+/// format as the bundled default, allowing updated listings to be loaded without recompiling.
+pub fn build_publisher_venue_map_from_reader(
+    reader: impl Read,
+) -> Result<IndexMap<PublisherId, Venue>> {
+    let rows: Vec<PublisherRow> = serde_json::from_reader(reader)?;
+    let mut map = IndexMap::with_capacity(rows.len());
+    for (i, row) in rows.into_iter().enumerate() {
+        let venue = row.venue.trim().to_ascii_uppercase();
+        let venue = venue.parse::<dbn::Venue>().map_err(|_| {
+            anyhow!(
+                "Unknown venue code '{}' for `publisher_id` {} in row {}",
+                row.venue,
+                row.publisher_id,
+                i + 1,
+            )
+        })?;
+        map.insert(row.publisher_id, Venue::from(venue.as_str()));
+    }
+    Ok(map)
+}
+
+/// Represents the differences from an old to a new publisher venue map.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapDiff {
+    /// The publishers only in the new map, in the order of the new map.
+    pub added: Vec<(PublisherId, Venue)>,
+    /// The publishers only in the old map, in the order of the old map.
+    pub removed: Vec<(PublisherId, Venue)>,
+    /// The publishers mapped to a different venue as `(publisher_id, old, new)`, in the order
+    /// of the new map.
+    pub changed: Vec<(PublisherId, Venue, Venue)>,
+}
+
+impl MapDiff {
+    /// Return whether the maps have no differences.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Returns the added, removed and changed entries from the `old` to the `new` publisher venue
+/// map, such as for validating an updated mapping before deployment.
+#[must_use]
+pub fn diff_publisher_venue_maps(
+    old: &IndexMap<PublisherId, Venue>,
+    new: &IndexMap<PublisherId, Venue>,
+) -> MapDiff {
+    let mut diff = MapDiff::default();
+    for (publisher_id, venue) in new {
+        match old.get(publisher_id) {
+            None => diff.added.push((*publisher_id, *venue)),
+            Some(old_venue) if old_venue != venue => {
+                diff.changed.push((*publisher_id, *old_venue, *venue));
+            }
+            Some(_) => {}
+        }
+    }
+    diff.removed = old
+        .iter()
+        .filter(|(publisher_id, _)| !new.contains_key(*publisher_id))
+        .map(|(publisher_id, venue)| (*publisher_id, *venue))
+        .collect();
+    diff
+}
+
+/// Returns all publisher IDs mapped to the given `venue`, in the order of the map.
+///
+/// This is the inverse of a `publisher_venue_map` lookup, with the `venue` normalized as for
+/// the map entries (see `normalize_venue`).
+#[must_use]
+pub fn publishers_for_venue(
+    venue: &Venue,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+) -> Vec<PublisherId> {
+    let venue = normalize_venue(*venue);
+    publisher_venue_map
+        .iter()
+        .filter(|(_, v)| **v == venue)
+        .map(|(publisher_id, _)| *publisher_id)
+        .collect()
+}
+
+/// Check that the `publisher_venue_map` covers every publisher of the given `dataset` (e.g.
+/// `GLBX.MDP3`) in the bundled publishers listing, so a missing venue mapping is caught at
+/// startup rather than when a record arrives.
+///
+/// # Errors
+///
+/// Returns the uncovered publisher IDs of the dataset, in the order of the listing.
+pub fn assert_dataset_coverage(
+    dataset: &str,
+    publisher_venue_map: &IndexMap<PublisherId, Venue>,
+) -> Result<(), Vec<PublisherId>> {
+    // SAFETY: Bundled table is known to be valid
+    let rows: Vec<PublisherRow> =
+        serde_json::from_str(PUBLISHERS_JSON).expect("Invalid bundled publishers");
+    let mut known_dataset = false;
+    let mut uncovered = Vec::new();
+    for row in rows.into_iter().filter(|row| row.dataset == dataset) {
+        known_dataset = true;
+        if !publisher_venue_map.contains_key(&row.publisher_id) {
+            uncovered.push(row.publisher_id);
+        }
+    }
+
+    if !known_dataset {
+        tracing::warn!("No publishers found for `dataset` {dataset} in the bundled listing");
+    }
+    if uncovered.is_empty() {
+        Ok(())
+    } else {
+        Err(uncovered)
+    }
+}
+
+/// Returns the venue for the given Databento numeric venue ID, from the venue table bundled
+/// with `dbn`.
+///
+/// This is an alternative to the `publisher_venue_map` for records which identify their venue
+/// directly, such as when the publisher map for a dataset is incomplete.
+pub fn venue_from_databento_venue_id(id: u16) -> Result<Venue> {
+    let venue = dbn::Venue::try_from(id).map_err(|_| anyhow!("Unknown Databento venue ID {id}"))?;
+    Ok(Venue::from(venue.as_str()))
+}
+
+/// Returns the product root of the given CME Globex outright contract `symbol` (e.g. `ES` for
+/// `ESH4` or `SR3` for `SR3H24`), or `None` if it has no contract month and year suffix.
+///
+/// Only the trailing one or two year digits and the month code are stripped, so roots which
+/// contain digits (such as `6E` or `SR3`) are preserved.
+#[must_use]
+pub fn glbx_product_root(symbol: &str) -> Option<&str> {
+    const MONTH_CODES: &[char] = &['F', 'G', 'H', 'J', 'K', 'M', 'N', 'Q', 'U', 'V', 'X', 'Z'];
+
+    let without_year = symbol.trim_end_matches(|c: char| c.is_ascii_digit());
+    if !(1..=2).contains(&(symbol.len() - without_year.len())) {
+        return None;
+    }
+
+    let root = without_year.strip_suffix(MONTH_CODES)?;
+    if root.is_empty() || !root.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(root)
+}
+
+/// Returns the trimmed uppercase `value`, or `None` if it is already normalized.
+fn normalize_code(value: &str) -> Option<String> {
+    let is_normalized = value.trim() == value && !value.bytes().any(|b| b.is_ascii_lowercase());
+    (!is_normalized).then(|| value.trim().to_ascii_uppercase())
+}
+
+fn parse_glbx_exchange_csv(content: &str) -> Result<Vec<GlbxExchangeRow>> {
+    let mut rows = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.starts_with("symbol")) {
+            continue; // Skip blank lines and header
+        }
+        let (symbol, venue) = line
+            .split_once(',')
+            .ok_or_else(|| anyhow!("Invalid GLBX exchange row {}: '{line}'", i + 1))?;
+        rows.push(GlbxExchangeRow {
+            symbol: symbol.trim().to_string(),
+            venue: venue.trim().to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+fn build_glbx_exchange_map(rows: Vec<GlbxExchangeRow>) -> Result<HashMap<Symbol, Venue>> {
+    let mut map = HashMap::with_capacity(rows.len());
+    for (i, row) in rows.into_iter().enumerate() {
+        if row.symbol.trim().is_empty() || row.venue.trim().is_empty() {
+            bail!(
+                "Invalid GLBX exchange row {}: symbol '{}', venue '{}'",
+                i + 1,
+                row.symbol,
+                row.venue,
+            );
+        }
+        let venue = Venue::from_code(&row.venue.trim().to_ascii_uppercase()).map_err(|_| {
+            anyhow!(
+                "Unknown GLBX exchange venue '{}' for symbol '{}' in row {}",
+                row.venue,
+                row.symbol,
+                i + 1,
+            )
+        })?;
+        map.insert(
+            normalize_symbol(Symbol::from_str_unchecked(&row.symbol)),
+            venue,
+        );
+    }
+    Ok(map)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use rstest::rstest;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    fn write_temp_file(suffix: &str, content: &str) -> NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    #[rstest]
+    #[case("ES", Venue::XCME())]
+    #[case("NQ", Venue::XCME())]
+    #[case("ZN", Venue::XCBT())]
+    #[case("CL", Venue::XNYM())]
+    #[case("GC", Venue::XCEC())]
+    fn test_default_glbx_exchange_map(#[case] symbol: &str, #[case] expected: Venue) {
+        let map = default_glbx_exchange_map();
+
+        assert_eq!(map.get(&Symbol::from(symbol)), Some(&expected));
+    }
+
+    #[rstest]
+    #[case(1, "GLBX")]
+    #[case(2, "XNAS")]
+    #[case(9, "XNYS")]
+    fn test_build_publisher_venue_map(#[case] publisher_id: PublisherId, #[case] expected: &str) {
+        let map = build_publisher_venue_map();
+
+        assert_eq!(map.get(&publisher_id), Some(&Venue::from(expected)));
+    }
+
+    #[rstest]
+    fn test_build_publisher_venue_map_from_reader() {
+        let json = r#"[{"publisher_id": 1, "dataset": "GLBX.MDP3", "venue": "GLBX", "description": "CME Globex MDP 3.0"}]"#;
+
+        let map = build_publisher_venue_map_from_reader(json.as_bytes()).unwrap();
+
+        assert_eq!(map, IndexMap::from([(1, Venue::GLBX())]));
+    }
+
+    #[rstest]
+    fn test_build_publisher_venue_map_from_reader_with_unknown_venue_returns_err() {
+        let json =
+            r#"[{"publisher_id": 1, "venue": "GLBX"}, {"publisher_id": 2, "venue": "ZZZZ"}]"#;
+
+        let result = build_publisher_venue_map_from_reader(json.as_bytes());
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unknown venue code 'ZZZZ' for `publisher_id` 2 in row 2"
+        );
+    }
+
+    #[rstest]
+    fn test_diff_publisher_venue_maps() {
+        let old = IndexMap::from([
+            (1, Venue::GLBX()),
+            (2, Venue::from("XNAS")),
+            (3, Venue::from("XBOS")),
+        ]);
+        let new = IndexMap::from([
+            (1, Venue::GLBX()),
+            (2, Venue::from("XNDQ")),
+            (4, Venue::from("XPSX")),
+        ]);
+
+        let diff = diff_publisher_venue_maps(&old, &new);
+
+        assert_eq!(diff.added, vec![(4, Venue::from("XPSX"))]);
+        assert_eq!(diff.removed, vec![(3, Venue::from("XBOS"))]);
+        assert_eq!(
+            diff.changed,
+            vec![(2, Venue::from("XNAS"), Venue::from("XNDQ"))]
+        );
+        assert!(!diff.is_empty());
+        assert!(diff_publisher_venue_maps(&old, &old).is_empty());
+    }
+
+    #[rstest]
+    fn test_publishers_for_venue() {
+        let map = IndexMap::from([
+            (3, Venue::GLBX()),
+            (2, Venue::from("XNAS")),
+            (1, Venue::GLBX()),
+        ]);
+
+        assert_eq!(publishers_for_venue(&Venue::GLBX(), &map), vec![3, 1]);
+        assert_eq!(publishers_for_venue(&Venue::from("xnas "), &map), vec![2]);
+        assert!(publishers_for_venue(&Venue::XCME(), &map).is_empty());
+    }
+
+    #[rstest]
+    fn test_publishers_for_venue_with_bundled_map() {
+        let map = build_publisher_venue_map();
+
+        let publisher_ids = publishers_for_venue(&Venue::GLBX(), &map);
+
+        assert!(publisher_ids.contains(&1));
+        assert!(publisher_ids
+            .iter()
+            .all(|publisher_id| map[publisher_id] == Venue::GLBX()));
+    }
+
+    #[rstest]
+    fn test_assert_dataset_coverage_with_bundled_map() {
+        let map = build_publisher_venue_map();
+
+        assert!(assert_dataset_coverage("GLBX.MDP3", &map).is_ok());
+        assert!(assert_dataset_coverage("DBEQ.BASIC", &map).is_ok());
+    }
+
+    #[rstest]
+    fn test_assert_dataset_coverage_reports_missing_publishers() {
+        let map = IndexMap::from([(2, Venue::from("XNAS")), (40, Venue::from("XCIS"))]);
+
+        assert_eq!(assert_dataset_coverage("GLBX.MDP3", &map), Err(vec![1]));
+        assert_eq!(
+            assert_dataset_coverage("DBEQ.BASIC", &map),
+            Err(vec![39, 41, 42, 59])
+        );
+    }
+
+    #[rstest]
+    #[case("ESH4", Some("ES"))]
+    #[case("ESZ24", Some("ES"))]
+    #[case("6EM4", Some("6E"))]
+    #[case("SR3H4", Some("SR3"))]
+    #[case("ES", None)]
+    #[case("SR3", None)]
+    #[case("ESH", None)]
+    #[case("H4", None)]
+    #[case("ESH4-ESM4", None)]
+    #[case("AAPL", None)]
+    fn test_glbx_product_root(#[case] symbol: &str, #[case] expected: Option<&str>) {
+        assert_eq!(glbx_product_root(symbol), expected);
+    }
+
+    #[rstest]
+    #[case(1, "GLBX")]
+    #[case(2, "XNAS")]
+    fn test_venue_from_databento_venue_id(#[case] id: u16, #[case] expected: &str) {
+        assert_eq!(
+            venue_from_databento_venue_id(id).unwrap(),
+            Venue::from(expected)
+        );
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(u16::MAX)]
+    fn test_venue_from_databento_venue_id_with_unknown_id_returns_err(#[case] id: u16) {
+        let err = venue_from_databento_venue_id(id).unwrap_err();
+
+        assert_eq!(err.to_string(), format!("Unknown Databento venue ID {id}"));
+    }
+
+    #[rstest]
+    fn test_load_glbx_exchange_map_csv() {
+        let file = write_temp_file(".csv", "symbol,venue\nESH4,XCME\nZNH4,XCBT\n");
+
+        let map = load_glbx_exchange_map(file.path()).unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&Symbol::from("ESH4")), Some(&Venue::XCME()));
+        assert_eq!(map.get(&Symbol::from("ZNH4")), Some(&Venue::XCBT()));
+    }
+
+    #[rstest]
+    fn test_load_glbx_exchange_map_json() {
+        let file = write_temp_file(".json", r#"[{"symbol": "CLH4", "venue": "XNYM"}]"#);
+
+        let map = load_glbx_exchange_map(file.path()).unwrap();
+
+        assert_eq!(map.get(&Symbol::from("CLH4")), Some(&Venue::XNYM()));
+    }
+
+    #[rstest]
+    fn test_load_glbx_exchange_map_with_blank_venue_returns_err() {
+        let file = write_temp_file(".csv", "symbol,venue\nESH4,XCME\nZNH4,\n");
+
+        let result = load_glbx_exchange_map(file.path());
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid GLBX exchange row 2: symbol 'ZNH4', venue ''"
+        );
+    }
+
+    #[rstest]
+    fn test_load_glbx_exchange_map_with_unknown_venue_returns_err() {
+        let file = write_temp_file(".csv", "ESH4,XXME\n");
+
+        let result = load_glbx_exchange_map(file.path());
+
+        assert!(result.unwrap_err().to_string().contains("'XXME'"));
+    }
+
+    #[rstest]
+    #[case("XCME")]
+    #[case("xcme")]
+    #[case("xcme ")]
+    #[case(" XCme\t")]
+    fn test_normalize_venue(#[case] value: &str) {
+        assert_eq!(normalize_venue(Venue::from(value)), Venue::XCME());
+    }
+
+    #[rstest]
+    fn test_normalize_glbx_exchange_map() {
+        let map = HashMap::from([(Symbol::from("es "), Venue::from("xcme "))]);
+
+        let map = normalize_glbx_exchange_map(map);
+
+        assert_eq!(map, HashMap::from([(Symbol::from("ES"), Venue::XCME())]));
+    }
+
+    #[rstest]
+    fn test_load_glbx_exchange_map_normalizes_rows() {
+        let file = write_temp_file(".json", r#"[{"symbol": "clh4 ", "venue": "xnym "}]"#);
+
+        let map = load_glbx_exchange_map(file.path()).unwrap();
+
+        assert_eq!(map.get(&Symbol::from("CLH4")), Some(&Venue::XNYM()));
+    }
+
+    #[rstest]
+    fn test_build_publisher_venue_map_from_reader_normalizes_venues() {
+        let json = r#"[{"publisher_id": 1, "venue": "glbx "}]"#;
+
+        let map = build_publisher_venue_map_from_reader(json.as_bytes()).unwrap();
+
+        assert_eq!(map, IndexMap::from([(1, Venue::GLBX())]));
+    }
+
+    #[rstest]
+    fn test_venue_maps_json_round_trip() {
+        let maps = VenueMaps {
+            publisher_venue_map: IndexMap::from([
+                (2, Venue::from("XNAS")),
+                (1, Venue::GLBX()),
+                (22, Venue::from("XCBO")),
+            ]),
+            glbx_exchange_map: HashMap::from([
+                (Symbol::from("ES"), Venue::XCME()),
+                (Symbol::from("ZN"), Venue::XCBT()),
+            ]),
+        };
+        let file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+
+        save_maps_to_json(&maps, file.path()).unwrap();
+        let loaded = load_maps_from_json(file.path()).unwrap();
+
+        assert_eq!(loaded, maps);
+        assert_eq!(
+            loaded
+                .publisher_venue_map
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![2, 1, 22]
+        );
+    }
+}
@@ -22,7 +22,7 @@ use anyhow::{anyhow, Result};
 use nautilus_core::correctness::check_valid_string;
 use ustr::Ustr;
 
-use crate::venues::VENUE_MAP;
+use crate::venues::{MIC_CODES, VENUE_MAP};
 
 pub const SYNTHETIC_VENUE: &str = "SYNTH";
 
@@ -64,6 +64,12 @@ impl Venue {
             .ok_or_else(|| anyhow!("Unknown venue code: {code}"))
     }
 
+    /// Return whether the venue is a known ISO 10383 market identifier code (MIC).
+    #[must_use]
+    pub fn is_mic(&self) -> bool {
+        MIC_CODES.binary_search(&self.value.as_str()).is_ok()
+    }
+
     #[must_use]
     pub fn synthetic() -> Self {
         // SAFETY: Unwrap safe as using known synthetic venue constant
@@ -116,4 +122,18 @@ mod tests {
         assert_eq!(venue_binance.to_string(), "BINANCE");
         assert_eq!(format!("{venue_binance}"), "BINANCE");
     }
+
+    #[rstest]
+    #[case("XCME", true)]
+    #[case("XNAS", true)]
+    #[case("XXME", false)]
+    #[case("BINANCE", false)]
+    fn test_is_mic(#[case] code: &str, #[case] expected: bool) {
+        assert_eq!(Venue::from(code).is_mic(), expected);
+    }
+
+    #[rstest]
+    fn test_mic_codes_sorted() {
+        assert!(crate::venues::MIC_CODES.windows(2).all(|w| w[0] < w[1]));
+    }
 }
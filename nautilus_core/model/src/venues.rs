@@ -95,3 +95,15 @@ pub static VENUE_MAP: Lazy<Mutex<HashMap<&str, Venue>>> = Lazy::new(|| {
     map.insert(Venue::XNYM().value.as_str(), Venue::XNYM());
     Mutex::new(map)
 });
+
+/// The bundled ISO 10383 market identifier codes (MICs), sorted for binary search.
+///
+/// This is the subset of operating and segment MICs for venues with supported data sources,
+/// rather than the full registry.
+pub static MIC_CODES: &[&str] = &[
+    "AMXO", "ARCO", "ARCX", "BATO", "BATS", "BATY", "C2OX", "CBCM", "EDGA", "EDGO", "EDGX", "EMLD",
+    "EPRL", "FINC", "FINN", "FINY", "GLBX", "GMNI", "IEXG", "IFEU", "IFUS", "MCRY", "MEMX", "MPRL",
+    "MXOP", "NDEX", "NYUM", "OPRA", "SPHR", "XASE", "XBOS", "XBOX", "XBXO", "XCBF", "XCBO", "XCBT",
+    "XCEC", "XCHI", "XCIS", "XCME", "XEUR", "XFXS", "XISX", "XLON", "XMIO", "XNAS", "XNDQ", "XNYM",
+    "XNYS", "XPHL", "XPSX", "XTSE",
+];